@@ -0,0 +1,51 @@
+use super::*;
+
+/// Bit level that signals an active alarm
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Polarity {
+    /// 1 = alarm
+    #[default]
+    ActiveHigh,
+    /// 0 = alarm
+    ActiveLow,
+}
+
+/// Names and polarities of alarm inputs, keyed by bit index within the unit
+#[derive(Clone, Debug, Default)]
+pub struct AlarmMap {
+    entries: Vec<(usize, String, Polarity)>,
+}
+
+impl AlarmMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, index: usize, name: impl Into<String>, polarity: Polarity) -> &mut Self {
+        self.entries.push((index, name.into(), polarity));
+        self
+    }
+}
+
+impl ModbusUnit {
+    /// Decode a coil/discrete-input response into `(name, active)` for every
+    /// alarm in `map`, applying each alarm's polarity
+    pub fn parse_alarms(&self, pdu: &[u8], map: &AlarmMap) -> Result<Vec<(String, bool)>, ModbusUnitError> {
+        if !self.is_bit_data() {
+            return Err(ModbusUnitError::NotBitData(self.register_type()));
+        }
+        let bits = self.decode_response(pdu, self.length())?;
+
+        map.entries
+            .iter()
+            .map(|(index, name, polarity)| {
+                let bit = *bits.get(*index).ok_or(ModbusUnitError::ReadIndexOutOfRange(*index))? != 0;
+                let active = match polarity {
+                    Polarity::ActiveHigh => bit,
+                    Polarity::ActiveLow => !bit,
+                };
+                Ok((name.clone(), active))
+            })
+            .collect()
+    }
+}
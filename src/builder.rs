@@ -0,0 +1,202 @@
+use super::*;
+
+/// Chaining setters every transport builder forwards to its `unit_builder`.
+/// Expands inside the builder's `impl` block.
+macro_rules! forward_unit_builder {
+    () => {
+        pub fn address(mut self, addr: i32) -> Self {
+            self.unit_builder.address(addr);
+            self
+        }
+
+        pub fn length(mut self, length: i32) -> Self {
+            self.unit_builder.length(length);
+            self
+        }
+
+        pub fn register_type(mut self, register_type: RegisterType) -> Self {
+            self.unit_builder.register_type(register_type);
+            self
+        }
+
+        pub fn with_read_cmd(mut self, spec_read_cmd: i32) -> Self {
+            self.unit_builder.with_read_cmd(spec_read_cmd);
+            self
+        }
+
+        pub fn with_write_cmd(mut self, spec_write_cmd: i32) -> Self {
+            self.unit_builder.with_write_cmd(spec_write_cmd);
+            self
+        }
+
+        pub fn with_multi_write_cmd(mut self, multi_write_cmd: i32) -> Self {
+            self.unit_builder.with_multi_write_cmd(multi_write_cmd);
+            self
+        }
+
+        pub fn custom_commands(mut self, read_cmd: i32, write_cmd: i32, multi_write_cmd: i32) -> Self {
+            self.unit_builder.custom_commands(read_cmd, write_cmd, multi_write_cmd);
+            self
+        }
+
+        pub fn force_multi_write(mut self, force_multi_write: bool) -> Self {
+            self.unit_builder.force_multi_write(force_multi_write);
+            self
+        }
+
+        pub fn sparse_writes(mut self, sparse_writes: bool) -> Self {
+            self.unit_builder.sparse_writes(sparse_writes);
+            self
+        }
+
+        pub fn coil_bit_order(mut self, coil_bit_order: BitOrder) -> Self {
+            self.unit_builder.coil_bit_order(coil_bit_order);
+            self
+        }
+
+        pub fn response_has_byte_count(mut self, response_has_byte_count: bool) -> Self {
+            self.unit_builder.response_has_byte_count(response_has_byte_count);
+            self
+        }
+
+        pub fn pad_short_reads(mut self, sentinel: u16) -> Self {
+            self.unit_builder.pad_short_reads(sentinel);
+            self
+        }
+
+        pub fn map_limits(mut self, min: u16, max: u16) -> Self {
+            self.unit_builder.map_limits(min, max);
+            self
+        }
+
+        pub fn wide_byte_count(mut self, wide_byte_count: bool) -> Self {
+            self.unit_builder.wide_byte_count(wide_byte_count);
+            self
+        }
+
+        pub fn run_indicator_position(mut self, run_indicator_pos: RunIndicatorPos) -> Self {
+            self.unit_builder.run_indicator_position(run_indicator_pos);
+            self
+        }
+
+        pub fn on_write_confirmed(mut self, callback: impl Fn(u16, u16) + Send + 'static) -> Self {
+            self.unit_builder.on_write_confirmed(callback);
+            self
+        }
+
+        pub fn skip_indices(mut self, indices: &[usize]) -> Self {
+            self.unit_builder.skip_indices(indices);
+            self
+        }
+
+        pub fn coil_true_value(mut self, coil_true_value: u16) -> Self {
+            self.unit_builder.coil_true_value(coil_true_value);
+            self
+        }
+
+        pub fn scaling(mut self, scale: f32, offset: f32) -> Self {
+            self.unit_builder.scaling(scale, offset);
+            self
+        }
+
+        pub fn count_field_unit(mut self, count_unit: CountUnit) -> Self {
+            self.unit_builder.count_field_unit(count_unit);
+            self
+        }
+
+        pub fn with_pad_byte(mut self, pad_byte: u8) -> Self {
+            self.unit_builder.with_pad_byte(pad_byte);
+            self
+        }
+
+        pub fn fault_on_uniform(mut self, fault_on_uniform: bool) -> Self {
+            self.unit_builder.fault_on_uniform(fault_on_uniform);
+            self
+        }
+
+        pub fn require_explicit_length(mut self, require_explicit_length: bool) -> Self {
+            self.unit_builder.require_explicit_length(require_explicit_length);
+            self
+        }
+
+        pub fn writable_range(mut self, min: u16, max: u16) -> Self {
+            self.unit_builder.writable_range(min, max);
+            self
+        }
+
+        pub fn word_order(mut self, word_order: WordOrder) -> Self {
+            self.unit_builder.word_order(word_order);
+            self
+        }
+
+        pub fn byte_order(mut self, byte_order: Endianness) -> Self {
+            self.unit_builder.byte_order(byte_order);
+            self
+        }
+    };
+}
+
+/// Build the unit and pair it with the device id, reporting a unit error
+/// together with a missing device id
+pub(crate) fn build_unit(unit_builder: ModbusUnitBuilder, device_id: Option<u8>) -> Result<(ModbusUnit, u8), ModbusTransportError> {
+    match (unit_builder.build(), device_id) {
+        (Ok(unit), Some(device_id)) => Ok((unit, device_id)),
+        (Err(err), None) => Err(ModbusTransportError::BuildErrors(vec![
+            ModbusTransportError::Protocol(err),
+            ModbusTransportError::DeviceIdMissing,
+        ])),
+        (Err(err), Some(_)) => Err(ModbusTransportError::Protocol(err)),
+        (Ok(_), None) => Err(ModbusTransportError::DeviceIdMissing),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_device_id_is_reported_with_unit_errors() {
+        let result = ModbusASCII::builder().length(1).build();
+        assert!(matches!(
+            result.as_ref().err(),
+            Some(ModbusTransportError::BuildErrors(errors)) if errors.len() == 2
+        ));
+
+        let result = ModbusTCPUnit::builder()
+            .address(0)
+            .register_type(RegisterType::HoldingRegister)
+            .build();
+        assert!(matches!(result, Err(ModbusTransportError::DeviceIdMissing)));
+    }
+
+    #[test]
+    fn forwarded_setters_reach_the_unit() {
+        let unit = ModbusRTU::builder()
+            .address(5)
+            .length(3)
+            .register_type(RegisterType::InputRegister)
+            .device_id(1)
+            .build()
+            .unwrap();
+        let request = unit.create_read_request().unwrap();
+        assert_eq!(&request[1..6], &[function_code::READ_INPUT_REGISTERS, 0x00, 0x05, 0x00, 0x03]);
+    }
+
+    #[test]
+    fn units_with_write_callbacks_are_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<ModbusUnit>();
+        assert_send::<ModbusTCPUnit>();
+        assert_send::<ModbusRTU>();
+        assert_send::<ModbusASCII>();
+
+        let unit = ModbusTCPUnit::builder()
+            .address(0)
+            .register_type(RegisterType::HoldingRegister)
+            .device_id(1)
+            .on_write_confirmed(|_, _| {})
+            .build()
+            .unwrap();
+        std::thread::spawn(move || unit.transaction_id()).join().unwrap();
+    }
+}
@@ -0,0 +1,166 @@
+/// Order of the two 16-bit words of a 32-bit value
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum WordOrder {
+    /// High word in the first register (ABCD), the most common layout
+    #[default]
+    HighWordFirst,
+    /// Low word in the first register (CDAB)
+    LowWordFirst,
+}
+
+/// Byte order of a multi-byte field
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Endianness {
+    #[default]
+    Big,
+    Little,
+}
+
+impl Endianness {
+    pub fn u16_to_bytes(&self, value: u16) -> [u8; 2] {
+        match self {
+            Endianness::Big => value.to_be_bytes(),
+            Endianness::Little => value.to_le_bytes(),
+        }
+    }
+
+    pub fn u16_from_bytes(&self, bytes: [u8; 2]) -> u16 {
+        match self {
+            Endianness::Big => u16::from_be_bytes(bytes),
+            Endianness::Little => u16::from_le_bytes(bytes),
+        }
+    }
+}
+
+pub fn u32_to_registers(value: u32, order: WordOrder) -> [u16; 2] {
+    let high = (value >> 16) as u16;
+    let low = value as u16;
+    match order {
+        WordOrder::HighWordFirst => [high, low],
+        WordOrder::LowWordFirst => [low, high],
+    }
+}
+
+pub fn registers_to_u32(regs: [u16; 2], order: WordOrder) -> u32 {
+    let (high, low) = match order {
+        WordOrder::HighWordFirst => (regs[0], regs[1]),
+        WordOrder::LowWordFirst => (regs[1], regs[0]),
+    };
+    ((high as u32) << 16) | low as u32
+}
+
+pub fn f32_to_registers(value: f32, order: WordOrder) -> [u16; 2] {
+    u32_to_registers(value.to_bits(), order)
+}
+
+pub fn registers_to_f32(regs: [u16; 2], order: WordOrder) -> f32 {
+    f32::from_bits(registers_to_u32(regs, order))
+}
+
+/// Split a 64-bit value into four registers. With big-endian bytes
+/// `A B C D E F G H`, `HighWordFirst` yields `[AB, CD, EF, GH]` and
+/// `LowWordFirst` yields `[GH, EF, CD, AB]`.
+pub fn u64_to_registers(value: u64, order: WordOrder) -> [u16; 4] {
+    let words = [(value >> 48) as u16, (value >> 32) as u16, (value >> 16) as u16, value as u16];
+    match order {
+        WordOrder::HighWordFirst => words,
+        WordOrder::LowWordFirst => [words[3], words[2], words[1], words[0]],
+    }
+}
+
+/// Inverse of `u64_to_registers`
+pub fn registers_to_u64(regs: [u16; 4], order: WordOrder) -> u64 {
+    let words = match order {
+        WordOrder::HighWordFirst => regs,
+        WordOrder::LowWordFirst => [regs[3], regs[2], regs[1], regs[0]],
+    };
+    words.iter().fold(0, |acc, &word| (acc << 16) | word as u64)
+}
+
+pub fn f64_to_registers(value: f64, order: WordOrder) -> [u16; 4] {
+    u64_to_registers(value.to_bits(), order)
+}
+
+pub fn registers_to_f64(regs: [u16; 4], order: WordOrder) -> f64 {
+    f64::from_bits(registers_to_u64(regs, order))
+}
+
+/// Split each register into two signed bytes. `Endianness::Big` yields the
+/// high byte first, `Endianness::Little` the low byte first.
+pub fn registers_to_i8_pairs(regs: &[u16], order: Endianness) -> Vec<i8> {
+    regs.iter()
+        .flat_map(|&reg| order.u16_to_bytes(reg))
+        .map(|byte| byte as i8)
+        .collect()
+}
+
+/// Pack signed bytes two per register, the inverse of `registers_to_i8_pairs`.
+/// An odd trailing value is paired with 0.
+pub fn i8_pairs_to_registers(values: &[i8], order: Endianness) -> Vec<u16> {
+    values
+        .chunks(2)
+        .map(|pair| order.u16_from_bytes([pair[0] as u8, pair.get(1).map_or(0, |&v| v as u8)]))
+        .collect()
+}
+
+/// Pack bytes two per register, first byte in the high half. An odd final
+/// byte is paired with `pad_byte`.
+pub fn bytes_to_registers(bytes: &[u8], pad_byte: u8) -> Vec<u16> {
+    bytes
+        .chunks(2)
+        .map(|pair| u16::from_be_bytes([pair[0], pair.get(1).copied().unwrap_or(pad_byte)]))
+        .collect()
+}
+
+/// Encode `text` as UTF-8 bytes packed two per register, see `bytes_to_registers`
+pub fn string_to_registers(text: &str, pad_byte: u8) -> Vec<u16> {
+    bytes_to_registers(text.as_bytes(), pad_byte)
+}
+
+/// Unix timestamp in seconds stored as a u32 across two registers
+pub fn registers_to_timestamp(regs: [u16; 2], order: WordOrder) -> u32 {
+    registers_to_u32(regs, order)
+}
+
+/// `registers_to_timestamp` as a UTC date and time
+#[cfg(feature = "chrono")]
+pub fn registers_to_datetime(regs: [u16; 2], order: WordOrder) -> Option<chrono::DateTime<chrono::Utc>> {
+    chrono::DateTime::from_timestamp(registers_to_timestamp(regs, order) as i64, 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timestamp_is_decoded_in_either_word_order() {
+        // 2023-11-14T22:13:20Z
+        assert_eq!(registers_to_timestamp([0x6553, 0xF100], WordOrder::HighWordFirst), 1_700_000_000);
+        assert_eq!(registers_to_timestamp([0xF100, 0x6553], WordOrder::LowWordFirst), 1_700_000_000);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn timestamp_converts_to_datetime() {
+        let datetime = registers_to_datetime([0x6553, 0xF100], WordOrder::HighWordFirst).unwrap();
+        assert_eq!(datetime.timestamp(), 1_700_000_000);
+    }
+
+    #[test]
+    fn i8_pairs_follow_byte_order() {
+        let table = [
+            (Endianness::Big, vec![0x7F80, 0x01FF], vec![127, -128, 1, -1]),
+            (Endianness::Little, vec![0x7F80, 0x01FF], vec![-128, 127, -1, 1]),
+        ];
+        for (order, registers, values) in table {
+            assert_eq!(registers_to_i8_pairs(&registers, order), values, "{order:?}");
+            assert_eq!(i8_pairs_to_registers(&values, order), registers, "{order:?}");
+        }
+    }
+
+    #[test]
+    fn odd_i8_value_is_paired_with_zero() {
+        assert_eq!(i8_pairs_to_registers(&[-1], Endianness::Big), vec![0xFF00]);
+        assert_eq!(i8_pairs_to_registers(&[-1], Endianness::Little), vec![0x00FF]);
+    }
+}
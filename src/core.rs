@@ -1,7 +1,13 @@
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+use std::time::Instant;
 use thiserror::Error;
-use std::ops::{Index, Range, RangeFrom, RangeTo, RangeFull};
-use crate::ModbusTransportError;
+use crate::function_code;
+use crate::convert::{
+    Endianness, WordOrder, bytes_to_registers, f32_to_registers, f64_to_registers, registers_to_f32,
+    registers_to_f64, registers_to_u32, registers_to_u64, u32_to_registers, u64_to_registers,
+};
 
 #[derive(Debug, Error)]
 pub enum ModbusUnitError {
@@ -14,6 +20,9 @@ pub enum ModbusUnitError {
     #[error("Invalid length: {0} < 0 or {0} > 65535")]
     InvalidLength(i32),
 
+    #[error("Length is empty")]
+    LengthIsEmpty,
+
     #[error("Invalid range: {0} + {1} = {2} > 65535")]
     RangeToMatch(i32, i32, i32),
 
@@ -21,6 +30,9 @@ pub enum ModbusUnitError {
     #[error("Register type is empty")]
     InvalidRegisterType,
 
+    #[error("Builder incomplete or invalid: {}", .0.join(", "))]
+    BuilderIncomplete(Vec<&'static str>),
+
     #[error("Read command {0} < 0 or {0} > 255")]
     InvalidReadCommand(i32),
 
@@ -33,6 +45,12 @@ pub enum ModbusUnitError {
     #[error("Type {0:?} haven't write command")]
     InvalidRegisterTypeForWriteCommand(RegisterType),
 
+    #[error("Type {0:?} doesn't hold bit data")]
+    NotBitData(RegisterType),
+
+    #[error("Type {0:?} doesn't hold register data")]
+    NotRegisterData(RegisterType),
+
     #[error("Invalid coil value {0} at index {1}, expected 0 or 1")]
     InvalidCoilValue(i32, usize),
 
@@ -45,6 +63,12 @@ pub enum ModbusUnitError {
     #[error("Unexpected function code: expected {0:#x}, got {1:#x}")]
     UnexpectedFunctionCode(u8, u8),
 
+    #[error("Invalid request length")]
+    InvalidRequestLength,
+
+    #[error("Unsupported function code {0:#x}")]
+    UnsupportedFunctionCode(u8),
+
     #[error("Invalid response length")]
     InvalidResponseLength,
 
@@ -59,9 +83,100 @@ pub enum ModbusUnitError {
 
     #[error("Write value not set at index {index} (address {address})")]
     WriteValueNotSet { index: usize, address: u16 },
+
+    #[error("Encoder error: byte count {actual} does not match expected {expected}")]
+    EncodedByteCountMismatch { expected: usize, actual: usize },
+
+    #[error("No write values set")]
+    NoWriteValuesSet,
+
+    #[error("Unit length {actual} does not match required {expected}")]
+    InvalidUnitLength { expected: u16, actual: u16 },
+
+    #[error("Value {value} at index {index} outside expected range")]
+    ValueOutOfExpectedRange { value: u16, index: usize },
+
+    #[error("Response count mismatch: expected {expected}, got {actual}")]
+    ResponseCountMismatch { expected: usize, actual: usize },
+
+    #[error("Echoed address mismatch: expected {expected}, received {received}")]
+    AddressEchoMismatch { expected: u16, received: u16 },
+
+    #[error("Echoed quantity mismatch: expected {expected}, received {received}")]
+    QuantityEchoMismatch { expected: u16, received: u16 },
+
+    #[error("Padding bits set in the last packed coil byte")]
+    PackedPaddingBitsSet,
+
+    #[error("Invalid single coil echo value {0:#06x}")]
+    InvalidCoilEchoValue(u16),
+
+    #[error("Coil round trip mismatch at index {index}: wrote {written}, read back {read}")]
+    CoilRoundTripMismatch { index: usize, written: u16, read: u16 },
+
+    #[error("Register count {count} is not a multiple of stride {stride}")]
+    InvalidStride { stride: usize, count: usize },
+
+    #[error("Address {addr} outside device map {min}..={max}")]
+    OutsideDeviceMap { addr: u16, min: u16, max: u16 },
+
+    #[error("Invalid run indicator {0:#04x}, expected 0x00 or 0xFF")]
+    InvalidRunIndicator(u8),
+
+    #[error("Double read mismatch: first {first:?}, second {second:?}")]
+    VerificationMismatch { first: Vec<u16>, second: Vec<u16> },
+
+    #[error("Skip index {index} out of range for length {length}")]
+    InvalidSkipIndex { index: usize, length: u16 },
+
+    #[error("Write verification failed: expected {expected:?}, read back {actual:?}")]
+    WriteVerificationFailed { expected: Vec<u16>, actual: Vec<u16> },
+
+    #[error("Read count {count} outside 1..={max}")]
+    InvalidReadCount { count: u16, max: u16 },
+
+    #[error("Suspected communication fault: every register is {pattern:#06x}")]
+    SuspectedCommFault { pattern: u16 },
+
+    #[error("Invalid layout field '{0}'")]
+    InvalidLayout(String),
+
+    #[error("Device responded but rejected address {0} (illegal data address)")]
+    ProbeAddressRejected(u16),
+
+    #[error("Address {addr} outside writable range {min}..={max}")]
+    WriteOutsideWritableRange { addr: u16, min: u16, max: u16 },
+
+    #[error("Mask write echo mismatch: sent AND {sent_and:#06x} OR {sent_or:#06x}, received AND {received_and:#06x} OR {received_or:#06x}")]
+    MaskEchoMismatch { sent_and: u16, sent_or: u16, received_and: u16, received_or: u16 },
+
+    #[error("Value overflow: {0} does not fit a 16-bit register at index {1}")]
+    ValueOverflow(i32, usize),
+
+    #[error("Echoed value mismatch: expected {expected:#06x}, received {received:#06x}")]
+    ValueEchoMismatch { expected: u16, received: u16 },
+
+    #[error("Unexpected MEI type: expected {expected:#04x}, got {received:#04x}")]
+    UnexpectedMeiType { expected: u8, received: u8 },
+
+    #[error("FIFO count {0} exceeds the maximum of 31")]
+    FifoCountTooLarge(u16),
+
+    #[error("Unit length {length} is not a multiple of {registers} registers per value")]
+    LengthNotMultiple { length: u16, registers: u16 },
+
+    #[error("Byte count mismatch: expected {expected}, received {received} for length {length}")]
+    ByteCountMismatch { expected: usize, received: usize, length: u16 },
+
+    #[error("No {0:#04x} request awaiting a response")]
+    NoPendingRequest(u8),
+
+    #[error("Truncated data: need {need} bytes, have {have}")]
+    TruncatedData { need: usize, have: usize },
 }
 
-#[derive(Copy, Clone, Debug, )]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum RegisterType {
     CoilRegister,
     DiscreteRegister,
@@ -69,6 +184,139 @@ pub enum RegisterType {
     InputRegister,
 }
 
+/// Position of the first coil within each packed byte
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum BitOrder {
+    /// First coil in bit 0, per the Modbus spec
+    #[default]
+    LsbFirst,
+    /// First coil in bit 7, for non-compliant devices
+    MsbFirst,
+}
+
+impl BitOrder {
+    fn bit_index(&self, i: usize) -> usize {
+        match self {
+            BitOrder::LsbFirst => i % 8,
+            BitOrder::MsbFirst => 7 - i % 8,
+        }
+    }
+}
+
+/// Result of `ModbusUnit::detect_byte_order`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ByteOrderGuess {
+    /// Values are plausible as received
+    AsIs,
+    /// Values are plausible only with high/low bytes swapped
+    Swapped,
+    /// Both or neither byte order gives plausible values
+    Ambiguous,
+}
+
+/// Where the run-indicator byte sits in a Report Server ID (0x11) payload
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum RunIndicatorPos {
+    /// Directly after a server ID of the given length, for devices that
+    /// append additional data after the run indicator
+    AfterId(usize),
+    /// Final byte of the payload, everything before it is the server ID
+    #[default]
+    Last,
+    /// Device sends no run indicator, the whole payload is the server ID
+    None,
+}
+
+/// Unit of the count field in register read responses
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum CountUnit {
+    /// Byte count, per the spec
+    #[default]
+    Bytes,
+    /// Register count, sent by some non-compliant devices
+    Registers,
+}
+
+impl CountUnit {
+    fn bytes_from_count(self, count: usize) -> usize {
+        match self {
+            CountUnit::Bytes => count,
+            CountUnit::Registers => count * 2,
+        }
+    }
+
+    fn count_from_bytes(self, byte_count: usize) -> usize {
+        match self {
+            CountUnit::Bytes => byte_count,
+            CountUnit::Registers => byte_count / 2,
+        }
+    }
+}
+
+/// Per-call choice between single (0x05/0x06) and multi (0x0F/0x10) write
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum WriteMode {
+    /// Follow `force_multi_write`
+    #[default]
+    Auto,
+    /// Always single write; only valid for one value
+    ForceSingle,
+    /// Always multi write, even for one value
+    ForceMulti,
+}
+
+/// One register of `ModbusUnit::parse_response_full`
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RegisterValue {
+    pub address: u16,
+    pub raw: u16,
+    /// `raw * scale + offset`, present only when `scaling` is configured
+    pub scaled: Option<f32>,
+}
+
+/// Coil transition reported by `ModbusUnit::parse_coils_edges`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Edge {
+    pub index: usize,
+    /// True for 0 -> 1, false for 1 -> 0
+    pub rising: bool,
+}
+
+/// Result of `ModbusUnit::parse_discrete_inputs_summary`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiscreteSummary {
+    pub active_count: usize,
+    /// Index of the lowest active input
+    pub first_active: Option<usize>,
+    pub bits: Vec<bool>,
+}
+
+/// Decoded Report Server ID (0x11) response
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ServerIdReport {
+    pub server_id: Vec<u8>,
+    /// `None` when the unit is configured with `RunIndicatorPos::None`
+    pub run_indicator: Option<bool>,
+    pub additional_data: Vec<u8>,
+}
+
+/// Decoded Read Device Identification (0x2B / 0x0E) response
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeviceIdentification {
+    pub conformity_level: u8,
+    /// Set when further objects must be read starting at `next_object_id`
+    pub more_follows: bool,
+    pub next_object_id: u8,
+    pub objects: Vec<(u8, Vec<u8>)>,
+}
+
+impl RegisterType {
+    /// True for single-bit data (coils and discrete inputs)
+    pub fn is_bit_type(&self) -> bool {
+        matches!(self, RegisterType::CoilRegister | RegisterType::DiscreteRegister)
+    }
+}
+
 pub struct ModbusUnit {
     start_addr: u16,
     length: u16,
@@ -76,9 +324,29 @@ pub struct ModbusUnit {
     read_cmd: Option<i32>,
     write_cmd: Option<i32>,
     multi_write_cmd: Option<i32>,
+    sparse_writes: bool,
+    coil_bit_order: BitOrder,
+    response_has_byte_count: bool,
+    short_read_pad: Option<u16>,
+    force_multi_write: bool,
+    wide_byte_count: bool,
+    run_indicator_pos: RunIndicatorPos,
+    on_write_confirmed: Option<Box<dyn Fn(u16, u16) + Send>>,
+    skip_indices: Vec<usize>,
+    coil_true_value: u16,
+    scaling: Option<(f32, f32)>,
+    count_unit: CountUnit,
+    pad_byte: u8,
+    fault_on_uniform: bool,
+    word_order: WordOrder,
+    byte_order: Endianness,
 
     write_vec: RefCell<Vec<Option<u16>>>,
     read_vec: RefCell<Vec<u16>>,
+    last_mask_write: Cell<Option<(u16, u16)>>,
+    last_read_write: Cell<Option<(u16, u16)>>,
+    last_single_write: Cell<Option<u16>>,
+    last_updated: Cell<Option<Instant>>,
 }
 
 pub struct ModbusUnitBuilder {
@@ -88,6 +356,25 @@ pub struct ModbusUnitBuilder {
     spec_read_cmd: Option<i32>,
     spec_write_cmd: Option<i32>,
     spec_multi_write_cmd: Option<i32>,
+    sparse_writes: bool,
+    coil_bit_order: BitOrder,
+    response_has_byte_count: bool,
+    short_read_pad: Option<u16>,
+    force_multi_write: bool,
+    map_limits: Option<(u16, u16)>,
+    wide_byte_count: bool,
+    run_indicator_pos: RunIndicatorPos,
+    on_write_confirmed: Option<Box<dyn Fn(u16, u16) + Send>>,
+    skip_indices: Vec<usize>,
+    coil_true_value: u16,
+    scaling: Option<(f32, f32)>,
+    count_unit: CountUnit,
+    pad_byte: u8,
+    fault_on_uniform: bool,
+    word_order: WordOrder,
+    byte_order: Endianness,
+    require_explicit_length: bool,
+    writable_range: Option<(u16, u16)>,
 }
 
 impl ModbusUnitBuilder {
@@ -121,10 +408,186 @@ impl ModbusUnitBuilder {
         self
     }
 
+    /// Override read, single-write and multi-write commands at once.
+    /// Each is range-checked in `build`: one invalid command gets its own
+    /// error, several give `BuilderIncomplete` listing every invalid field.
+    pub fn custom_commands(&mut self, read_cmd: i32, write_cmd: i32, multi_write_cmd: i32) -> &mut Self {
+        self.spec_read_cmd = Some(read_cmd);
+        self.spec_write_cmd = Some(write_cmd);
+        self.spec_multi_write_cmd = Some(multi_write_cmd);
+        self
+    }
+
+    /// Emit one single-write request per set index instead of requiring the whole range
+    pub fn sparse_writes(&mut self, sparse_writes: bool) -> &mut Self {
+        self.sparse_writes = sparse_writes;
+        self
+    }
+
+    pub fn coil_bit_order(&mut self, coil_bit_order: BitOrder) -> &mut Self {
+        self.coil_bit_order = coil_bit_order;
+        self
+    }
+
+    /// Set to false for non-compliant devices that send register data directly
+    /// after the function code without a ByteCount field
+    pub fn response_has_byte_count(&mut self, response_has_byte_count: bool) -> &mut Self {
+        self.response_has_byte_count = response_has_byte_count;
+        self
+    }
+
+    /// Vendor extension: register responses carry a two-byte ByteCount,
+    /// with data starting at offset 3
+    pub fn wide_byte_count(&mut self, wide_byte_count: bool) -> &mut Self {
+        self.wide_byte_count = wide_byte_count;
+        self
+    }
+
+    /// Make an unset `length` a build error instead of defaulting to 1
+    pub fn require_explicit_length(&mut self, require_explicit_length: bool) -> &mut Self {
+        self.require_explicit_length = require_explicit_length;
+        self
+    }
+
+    /// Reject register responses where every value is 0x0000 or every value
+    /// is 0xFFFF, the usual reading of a disconnected sensor
+    pub fn fault_on_uniform(&mut self, fault_on_uniform: bool) -> &mut Self {
+        self.fault_on_uniform = fault_on_uniform;
+        self
+    }
+
+    /// Word order of values spread over several registers, default high word first
+    pub fn word_order(&mut self, word_order: WordOrder) -> &mut Self {
+        self.word_order = word_order;
+        self
+    }
+
+    /// Byte order inside each register of a multi-register value, default
+    /// big-endian. `Little` swaps the two bytes of every register.
+    pub fn byte_order(&mut self, byte_order: Endianness) -> &mut Self {
+        self.byte_order = byte_order;
+        self
+    }
+
+    /// Filler for unused bytes in string and byte writes, default 0x00.
+    /// Some devices expect 0x20 (space) for text fields.
+    pub fn with_pad_byte(&mut self, pad_byte: u8) -> &mut Self {
+        self.pad_byte = pad_byte;
+        self
+    }
+
+    /// Whether register responses report their data length in bytes (default)
+    /// or in registers
+    pub fn count_field_unit(&mut self, count_unit: CountUnit) -> &mut Self {
+        self.count_unit = count_unit;
+        self
+    }
+
+    /// Linear conversion to engineering units: `raw * scale + offset`
+    pub fn scaling(&mut self, scale: f32, offset: f32) -> &mut Self {
+        self.scaling = Some((scale, offset));
+        self
+    }
+
+    /// Value written by Write Single Coil (0x05) for an ON coil. Default 0xFF00
+    /// per spec; override only for non-compliant devices.
+    pub fn coil_true_value(&mut self, coil_true_value: u16) -> &mut Self {
+        self.coil_true_value = coil_true_value;
+        self
+    }
+
+    /// Reserved positions within the block, reported as `None` by
+    /// `parse_response_filtered`. Each index must be below `length`.
+    pub fn skip_indices(&mut self, indices: &[usize]) -> &mut Self {
+        self.skip_indices = indices.to_vec();
+        self
+    }
+
+    /// Callback invoked with `(start_addr, quantity)` whenever a write
+    /// acknowledgement parses successfully, e.g. for audit logging
+    pub fn on_write_confirmed(&mut self, callback: impl Fn(u16, u16) + Send + 'static) -> &mut Self {
+        self.on_write_confirmed = Some(Box::new(callback));
+        self
+    }
+
+    /// Position of the run indicator in Report Server ID responses,
+    /// default `RunIndicatorPos::Last`
+    pub fn run_indicator_position(&mut self, run_indicator_pos: RunIndicatorPos) -> &mut Self {
+        self.run_indicator_pos = run_indicator_pos;
+        self
+    }
+
+    /// Accept register responses shorter than `length`, filling the missing
+    /// trailing registers with `sentinel` instead of failing
+    pub fn pad_short_reads(&mut self, sentinel: u16) -> &mut Self {
+        self.short_read_pad = Some(sentinel);
+        self
+    }
+
+    /// Use the multi-write command (0x0F/0x10) even for a single value, for
+    /// devices that reject single writes, instead of overriding the command
+    /// with `with_write_cmd`. By default one value uses single write
+    /// (0x05/0x06) and more use multi-write; since a single-write PDU carries
+    /// exactly one value, this is the only other policy there is.
+    pub fn force_multi_write(&mut self, force_multi_write: bool) -> &mut Self {
+        self.force_multi_write = force_multi_write;
+        self
+    }
+
+    /// Inclusive address range the device actually implements; `build` rejects
+    /// units that start below `min` or end above `max`
+    pub fn map_limits(&mut self, min: u16, max: u16) -> &mut Self {
+        self.map_limits = Some((min, max));
+        self
+    }
+
+    /// Inclusive address range the device accepts writes to; `build` rejects
+    /// writable units (coils, holding registers) reaching outside it
+    pub fn writable_range(&mut self, min: u16, max: u16) -> &mut Self {
+        self.writable_range = Some((min, max));
+        self
+    }
+
+    /// Names of all fields that are missing or out of range
+    fn invalid_fields(&self) -> Vec<&'static str> {
+        let out_of = |value: Option<i32>, max: i32| value.is_some_and(|v| !(0..=max).contains(&v));
+
+        let mut fields = Vec::new();
+        if self.start_addr.is_none() || out_of(self.start_addr, 65535) {
+            fields.push("address");
+        }
+        if self.register_type.is_none() {
+            fields.push("register_type");
+        }
+        if out_of(self.length, 65535) || (self.require_explicit_length && self.length.is_none()) {
+            fields.push("length");
+        }
+        if out_of(self.spec_read_cmd, 255) {
+            fields.push("read_cmd");
+        }
+        if out_of(self.spec_write_cmd, 255) {
+            fields.push("write_cmd");
+        }
+        if out_of(self.spec_multi_write_cmd, 255) {
+            fields.push("multi_write_cmd");
+        }
+        let length = self.length.unwrap_or(1);
+        if self.skip_indices.iter().any(|&index| index as i64 >= length as i64) {
+            fields.push("skip_indices");
+        }
+        fields
+    }
+
     pub fn build(self) -> Result<ModbusUnit, ModbusUnitError> {
+        // Report every problem at once; a single problem keeps its specific error below
+        let invalid_fields = self.invalid_fields();
+        if invalid_fields.len() > 1 {
+            return Err(ModbusUnitError::BuilderIncomplete(invalid_fields));
+        }
+
         let start_addr = match self.start_addr {
             Some(addr) => {
-                if addr < 0 || addr > 65535 {
+                if !(0..=65535).contains(&addr) {
                     return Err(ModbusUnitError::InvalidAddress(addr));
                 }
                 addr
@@ -137,11 +600,12 @@ impl ModbusUnitBuilder {
         };
         let length = match self.length {
             Some(length) => {
-                if length < 0 || length > 65535 {
+                if !(0..=65535).contains(&length) {
                     return Err(ModbusUnitError::InvalidLength(length));
                 }
                 length
             },
+            None if self.require_explicit_length => return Err(ModbusUnitError::LengthIsEmpty),
             None => 1,
         };
         let end_addr = start_addr + length;
@@ -150,7 +614,7 @@ impl ModbusUnitBuilder {
         }
         let read_cmd = match self.spec_read_cmd {
             Some(spec_read_cmd) => {
-                if spec_read_cmd < 0 || spec_read_cmd > 255 {
+                if !(0..=255).contains(&spec_read_cmd) {
                     return Err(ModbusUnitError::InvalidReadCommand(spec_read_cmd));
                 }
                 Some(spec_read_cmd)
@@ -159,7 +623,7 @@ impl ModbusUnitBuilder {
         };
         let write_cmd = match self.spec_write_cmd {
             Some(spec_write_cmd) => {
-                if spec_write_cmd < 0 || spec_write_cmd > 255 {
+                if !(0..=255).contains(&spec_write_cmd) {
                     return Err(ModbusUnitError::InvalidWriteCommand(spec_write_cmd));
                 }
                 Some(spec_write_cmd)
@@ -168,13 +632,36 @@ impl ModbusUnitBuilder {
         };
         let multi_write_cmd = match self.spec_multi_write_cmd {
             Some(spec_multi_write_cmd) => {
-                if spec_multi_write_cmd < 0 || spec_multi_write_cmd > 255 {
+                if !(0..=255).contains(&spec_multi_write_cmd) {
                     return Err(ModbusUnitError::InvalidWriteMultiCommand(spec_multi_write_cmd));
                 }
                 Some(spec_multi_write_cmd)
             },
             None => None,
         };
+        if let Some(&index) = self.skip_indices.iter().find(|&&index| index >= length as usize) {
+            return Err(ModbusUnitError::InvalidSkipIndex { index, length: length as u16 });
+        }
+        let last_addr = (start_addr + length - 1).max(start_addr) as u16;
+        if let Some((min, max)) = self.map_limits {
+            if (start_addr as u16) < min {
+                return Err(ModbusUnitError::OutsideDeviceMap { addr: start_addr as u16, min, max });
+            }
+            if last_addr > max {
+                return Err(ModbusUnitError::OutsideDeviceMap { addr: last_addr, min, max });
+            }
+        }
+        let writable_type = matches!(reg_type, RegisterType::CoilRegister | RegisterType::HoldingRegister);
+        if let Some((min, max)) = self.writable_range
+            && writable_type
+        {
+            if (start_addr as u16) < min {
+                return Err(ModbusUnitError::WriteOutsideWritableRange { addr: start_addr as u16, min, max });
+            }
+            if last_addr > max {
+                return Err(ModbusUnitError::WriteOutsideWritableRange { addr: last_addr, min, max });
+            }
+        }
 
         let write_vec: Vec<Option<u16>> = vec![None; length as usize];
         let read_vec: Vec<u16> = vec![0; length as usize];
@@ -184,11 +671,31 @@ impl ModbusUnitBuilder {
                 start_addr: start_addr as u16,
                 length: length as u16,
                 register_type: reg_type,
-                read_cmd: read_cmd,
-                write_cmd: write_cmd,
-                multi_write_cmd: multi_write_cmd,
+                read_cmd,
+                write_cmd,
+                multi_write_cmd,
+                sparse_writes: self.sparse_writes,
+                coil_bit_order: self.coil_bit_order,
+                response_has_byte_count: self.response_has_byte_count,
+                short_read_pad: self.short_read_pad,
+                force_multi_write: self.force_multi_write,
+                wide_byte_count: self.wide_byte_count,
+                run_indicator_pos: self.run_indicator_pos,
+                on_write_confirmed: self.on_write_confirmed,
+                skip_indices: self.skip_indices,
+                coil_true_value: self.coil_true_value,
+                scaling: self.scaling,
+                count_unit: self.count_unit,
+                pad_byte: self.pad_byte,
+                fault_on_uniform: self.fault_on_uniform,
+                word_order: self.word_order,
+                byte_order: self.byte_order,
                 write_vec: RefCell::new(write_vec),
                 read_vec: RefCell::new(read_vec),
+                last_mask_write: Cell::new(None),
+                last_read_write: Cell::new(None),
+                last_single_write: Cell::new(None),
+                last_updated: Cell::new(None),
             }
         )
     }
@@ -203,29 +710,186 @@ impl ModbusUnit {
             spec_read_cmd: None,
             spec_write_cmd: None,
             spec_multi_write_cmd: None,
+            sparse_writes: false,
+            coil_bit_order: BitOrder::LsbFirst,
+            response_has_byte_count: true,
+            short_read_pad: None,
+            force_multi_write: false,
+            map_limits: None,
+            wide_byte_count: false,
+            run_indicator_pos: RunIndicatorPos::default(),
+            on_write_confirmed: None,
+            skip_indices: Vec::new(),
+            coil_true_value: 0xFF00,
+            scaling: None,
+            count_unit: CountUnit::Bytes,
+            pad_byte: 0x00,
+            fault_on_uniform: false,
+            word_order: WordOrder::default(),
+            byte_order: Endianness::default(),
+            require_explicit_length: false,
+            writable_range: None,
+        }
+    }
+
+    pub fn start_addr(&self) -> u16 {
+        self.start_addr
+    }
+
+    pub fn length(&self) -> u16 {
+        self.length
+    }
+
+    pub fn register_type(&self) -> RegisterType {
+        self.register_type
+    }
+
+    /// True when both units use the same register type and command
+    /// overrides, so they could share a request path or be merged
+    pub fn is_compatible_with(&self, other: &ModbusUnit) -> bool {
+        self.register_type == other.register_type
+            && self.read_cmd == other.read_cmd
+            && self.write_cmd == other.write_cmd
+            && self.multi_write_cmd == other.multi_write_cmd
+    }
+
+    /// Conventional data address (0xxxx coils, 1xxxx discrete inputs,
+    /// 3xxxx input registers, 4xxxx holding registers), one-based.
+    /// Offsets above 9999 use the six-digit form, e.g. 410000.
+    pub fn to_data_address(&self) -> u32 {
+        let prefix: u32 = match self.register_type {
+            RegisterType::CoilRegister => 0,
+            RegisterType::DiscreteRegister => 1,
+            RegisterType::InputRegister => 3,
+            RegisterType::HoldingRegister => 4,
+        };
+        let offset = self.start_addr as u32 + 1;
+        if offset <= 9999 {
+            prefix * 10_000 + offset
+        } else {
+            prefix * 100_000 + offset
+        }
+    }
+
+    /// Spec limit for a single read: 2000 bits or 125 registers
+    pub fn max_read_quantity(&self) -> u16 {
+        if self.is_bit_data() { 2000 } else { 125 }
+    }
+
+    /// Spec limit for a single multi-write: 1968 coils or 123 registers.
+    /// Read-only types return 0.
+    pub fn max_write_quantity(&self) -> u16 {
+        match self.register_type {
+            RegisterType::CoilRegister => 1968,
+            RegisterType::HoldingRegister => 123,
+            _ => 0,
         }
     }
 
+    /// True when the unit reads coils or discrete inputs rather than registers
+    pub fn is_bit_data(&self) -> bool {
+        self.register_type.is_bit_type()
+    }
+
+    /// Flat constructor for FFI callers, validated exactly like `build()`
+    pub fn from_parts(start_addr: u16, length: u16, register_type: RegisterType) -> Result<ModbusUnit, ModbusUnitError> {
+        let mut builder = Self::builder();
+        builder
+            .address(start_addr as i32)
+            .length(length as i32)
+            .register_type(register_type);
+        builder.build()
+    }
+
     pub fn create_read_request(&self) -> Result<Vec<u8>, ModbusUnitError> {
+        Ok(self.read_request_pdu(self.start_addr, self.length)) // no err. all data for read validate in builder
+    }
+
+    /// Read request for only the first `count` values of the unit
+    pub fn create_read_request_count(&self, count: u16) -> Result<Vec<u8>, ModbusUnitError> {
+        self.check_read_count(count)?;
+        Ok(self.read_request_pdu(self.start_addr, count))
+    }
+
+    /// Parse the response to `create_read_request_count`, storing the first `count` values
+    pub fn parse_response_count(&self, pdu: &[u8], count: u16) -> Result<Vec<u16>, ModbusUnitError> {
+        self.check_read_count(count)?;
+        let values = self.decode_response(pdu, count)?;
+        self.store_values(&values);
+        Ok(values)
+    }
+
+    /// Lightest possible liveness check: read one value at `start_addr`
+    pub fn create_probe_request(&self) -> Result<Vec<u8>, ModbusUnitError> {
+        Ok(self.read_request_pdu(self.start_addr, 1))
+    }
+
+    /// True for a valid probe response, false for any exception other than
+    /// IllegalDataAddress, which errors with `ProbeAddressRejected` since the
+    /// device is present but misconfigured. Stores nothing.
+    pub fn parse_probe_response(&self, pdu: &[u8]) -> Result<bool, ModbusUnitError> {
+        match self.response_data(pdu, 1) {
+            Ok(_) => Ok(true),
+            Err(ModbusUnitError::ModbusException(_, 0x02)) => Err(ModbusUnitError::ProbeAddressRejected(self.start_addr)),
+            Err(ModbusUnitError::ModbusException(_, _)) => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn check_read_count(&self, count: u16) -> Result<(), ModbusUnitError> {
+        let max = self.length.min(self.max_read_quantity());
+        if !(1..=max).contains(&count) {
+            return Err(ModbusUnitError::InvalidReadCount { count, max });
+        }
+        Ok(())
+    }
+
+    fn read_request_pdu(&self, address: u16, quantity: u16) -> Vec<u8> {
         let mut msg: [u8; 5] = [0; 5];
         let command = match self.read_cmd {
             Some(cmd) => cmd as u8,
             None => self.get_read_command(),
         };
         msg[0] = command;
-        msg[1] = (self.start_addr >> 8) as u8;
-        msg[2] = self.start_addr as u8;
-        msg[3] = (self.length >> 8) as u8;
-        msg[4] = self.length as u8;
-        Ok(Vec::from(msg)) // no err. all data for read validate in builder
+        msg[1] = (address >> 8) as u8;
+        msg[2] = address as u8;
+        msg[3] = (quantity >> 8) as u8;
+        msg[4] = quantity as u8;
+        Vec::from(msg)
+    }
+
+    /// Number of requests `create_read_requests` produces for this unit
+    pub fn read_transaction_count(&self) -> usize {
+        (self.length as usize)
+            .div_ceil(self.max_read_quantity() as usize)
+            .max(1)
+    }
+
+    /// Address and quantity of each chunk of a large read
+    fn read_chunks(&self) -> Vec<(u16, u16)> {
+        let max = self.max_read_quantity();
+        (0..self.read_transaction_count())
+            .map(|i| {
+                let offset = i as u16 * max;
+                (self.start_addr + offset, (self.length - offset).min(max))
+            })
+            .collect()
+    }
+
+    /// Split the read into request PDUs that respect `max_read_quantity`
+    pub fn create_read_requests(&self) -> Result<Vec<Vec<u8>>, ModbusUnitError> {
+        Ok(self.read_chunks()
+            .into_iter()
+            .map(|(address, quantity)| self.read_request_pdu(address, quantity))
+            .collect())
     }
 
     fn get_read_command(&self) -> u8 {
         match self.register_type {
-            RegisterType::CoilRegister => 0x01,
-            RegisterType::DiscreteRegister => 0x02,
-            RegisterType::HoldingRegister => 0x03,
-            RegisterType::InputRegister => 0x04,
+            RegisterType::CoilRegister => function_code::READ_COILS,
+            RegisterType::DiscreteRegister => function_code::READ_DISCRETE_INPUTS,
+            RegisterType::HoldingRegister => function_code::READ_HOLDING_REGISTERS,
+            RegisterType::InputRegister => function_code::READ_INPUT_REGISTERS,
         }
     }
 
@@ -243,14 +907,38 @@ impl ModbusUnit {
             })
             .collect::<Result<Vec<_>, _>>()?;
 
-        let cmd = self.get_write_command(validated_data.len())?;
+        self.get_write_request_using(&validated_data, WriteMode::Auto)
+    }
+
+    /// Build a write request PDU for `data` without touching the staged values,
+    /// choosing single or multi write per `mode`. `data` must cover the whole unit.
+    pub fn get_write_request_using(&self, data: &[u16], mode: WriteMode) -> Result<Vec<u8>, ModbusUnitError> {
+        if data.len() != self.length as usize {
+            return Err(ModbusUnitError::DataLengthMismatch {
+                expected: self.length as usize,
+                actual: data.len(),
+            });
+        }
+        let multi = match mode {
+            WriteMode::Auto => self.is_multi_write(data.len()),
+            WriteMode::ForceSingle if data.len() > 1 => {
+                return Err(ModbusUnitError::DataLengthMismatch { expected: 1, actual: data.len() });
+            }
+            WriteMode::ForceSingle => false,
+            WriteMode::ForceMulti => true,
+        };
+        let cmd = if multi {
+            self.get_multi_write_command()?
+        } else {
+            self.get_single_write_command()?
+        };
 
         match self.register_type {
             RegisterType::CoilRegister => {
-                self.get_for_body_for_coils_write(&validated_data, cmd)
+                self.get_for_body_for_coils_write(data, cmd, multi)
             }
             RegisterType::HoldingRegister => {
-                self.get_for_body_for_holding_write(&validated_data, cmd)
+                self.get_for_body_for_holding_write(data, cmd, multi)
             }
             _ => Err(ModbusUnitError::InvalidRegisterTypeForWriteCommand(
                 self.register_type,
@@ -258,179 +946,1342 @@ impl ModbusUnit {
         }
     }
 
-    fn get_write_command(&self, length: usize) -> Result<u8, ModbusUnitError> {
-        match length {
-            1 => self.get_single_write_command(),
-            _ => self.get_multi_write_command()
+    /// Build write request PDUs. In sparse mode every set index gets its own
+    /// single-write PDU, otherwise this is the single contiguous write request.
+    pub fn get_write_requests(&self) -> Result<Vec<Vec<u8>>, ModbusUnitError> {
+        if !self.sparse_writes {
+            return Ok(vec![self.get_write_request()?]);
         }
-    }
 
-    fn get_single_write_command(&self) -> Result<u8, ModbusUnitError> {
-        let cmd = match self.write_cmd {
-            Some(cmd) => cmd as u8,
-            None => match self.register_type {
-                RegisterType::CoilRegister => 0x05,
-                RegisterType::HoldingRegister => 0x06,
-                _ => return Err(ModbusUnitError::InvalidRegisterTypeForWriteCommand(self.register_type))
-            }
-        };
-        Ok(cmd)
+        let cmd = self.get_single_write_command()?;
+        let requests = self.write_vec
+            .borrow()
+            .iter()
+            .enumerate()
+            .filter_map(|(i, opt)| opt.map(|value| (i, value)))
+            .map(|(i, value)| self.get_single_write_body(self.start_addr + i as u16, value, cmd))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if requests.is_empty() {
+            return Err(ModbusUnitError::NoWriteValuesSet);
+        }
+        Ok(requests)
     }
 
-    fn get_multi_write_command(&self) -> Result<u8, ModbusUnitError> {
-        let cmd = match self.multi_write_cmd {
-            Some(cmd) => cmd as u8,
-            None => match self.register_type {
-                RegisterType::CoilRegister => 0x0F,
-                RegisterType::HoldingRegister => 0x10,
-                _ => return Err(ModbusUnitError::InvalidRegisterTypeForWriteCommand(self.register_type))
-            }
-        };
-        Ok(cmd)
+    /// Build a Mask Write Register (0x16) PDU for `start_addr`. The device
+    /// stores `(current AND and_mask) OR (or_mask AND NOT and_mask)`.
+    pub fn get_mask_write_request(&self, and_mask: u16, or_mask: u16) -> Result<Vec<u8>, ModbusUnitError> {
+        if !matches!(self.register_type, RegisterType::HoldingRegister) {
+            return Err(ModbusUnitError::InvalidRegisterTypeForWriteCommand(self.register_type));
+        }
+        self.last_mask_write.set(Some((and_mask, or_mask)));
+
+        let mut msg = Vec::with_capacity(7);
+        msg.push(function_code::MASK_WRITE_REGISTER);
+        msg.extend(self.start_addr.to_be_bytes());
+        msg.extend(and_mask.to_be_bytes());
+        msg.extend(or_mask.to_be_bytes());
+        Ok(msg)
     }
 
-    fn get_for_body_for_holding_write(&self, data: &[u16], cmd: u8) -> Result<Vec<u8>, ModbusUnitError> {
-        let request_len = 3 + { if data.len() == 1 {2} else {3 + data.len() * 2} };
-        let mut result: Vec<u8> = Vec::with_capacity(request_len);
-        result.push(cmd);
-        result.push((self.start_addr >> 8) as u8);
-        result.push(self.start_addr as u8);
+    /// Parse a Mask Write Register (0x16) echo, checking the address and that
+    /// both masks match the last `get_mask_write_request`, which is consumed
+    pub fn parse_mask_write_response(&self, pdu: &[u8]) -> Result<(), ModbusUnitError> {
+        let (sent_and, sent_or) = self.last_mask_write
+            .take()
+            .ok_or(ModbusUnitError::NoPendingRequest(function_code::MASK_WRITE_REGISTER))?;
+        Self::check_function_code(pdu, function_code::MASK_WRITE_REGISTER)?;
+        self.check_echoed_address(pdu)?;
+        if pdu.len() < 7 {
+            return Err(ModbusUnitError::InvalidResponseLength);
+        }
 
-        if data.len() > 1 {
-            result.push((data.len() >> 8) as u8);
-            result.push(data.len() as u8);
-            result.push((data.len() * 2) as u8);  // ← ДОДАНО ByteCount!
+        let received_and = ((pdu[3] as u16) << 8) | (pdu[4] as u16);
+        let received_or = ((pdu[5] as u16) << 8) | (pdu[6] as u16);
+        if sent_and != received_and || sent_or != received_or {
+            return Err(ModbusUnitError::MaskEchoMismatch {
+                sent_and,
+                sent_or,
+                received_and,
+                received_or,
+            });
         }
+        self.confirm_write(self.start_addr, 1);
+        Ok(())
+    }
 
-        for &item in data.iter() {
-            result.push((item >> 8) as u8);
-            result.push(item as u8);
+    /// Build a Write Single Register (0x06) PDU for `start_addr`, regardless
+    /// of the unit length or `force_multi_write`
+    pub fn get_write_single_register(&self, value: i32) -> Result<Vec<u8>, ModbusUnitError> {
+        if !matches!(self.register_type, RegisterType::HoldingRegister) {
+            return Err(ModbusUnitError::InvalidRegisterTypeForWriteCommand(self.register_type));
         }
-        Ok(result)
+        let value = Self::register_word(value, 0)?;
+        self.last_single_write.set(Some(value));
+
+        let mut msg = Vec::with_capacity(5);
+        msg.push(function_code::WRITE_SINGLE_REGISTER);
+        msg.extend(self.start_addr.to_be_bytes());
+        msg.extend(value.to_be_bytes());
+        Ok(msg)
     }
 
-    fn get_for_body_for_coils_write(&self, data: &[u16], cmd: u8) -> Result<Vec<u8>, ModbusUnitError> {
-        // Validate: all values must be 0 or 1
-        for (i, &val) in data.iter().enumerate() {
-            if val != 0 && val != 1 {
-                return Err(ModbusUnitError::InvalidCoilValue(val as i32, i));
-            }
+    /// Parse a Write Single Register (0x06) echo, checking the address and
+    /// the value sent by the last `get_write_single_register`, which is consumed
+    pub fn parse_write_single_register_response(&self, pdu: &[u8]) -> Result<(), ModbusUnitError> {
+        let expected = self.last_single_write
+            .take()
+            .ok_or(ModbusUnitError::NoPendingRequest(function_code::WRITE_SINGLE_REGISTER))?;
+        Self::check_function_code(pdu, function_code::WRITE_SINGLE_REGISTER)?;
+        self.check_echoed_address(pdu)?;
+
+        let received = ((pdu[3] as u16) << 8) | (pdu[4] as u16);
+        if expected != received {
+            return Err(ModbusUnitError::ValueEchoMismatch { expected, received });
         }
+        self.confirm_write(self.start_addr, 1);
+        Ok(())
+    }
 
-        let capacity = if data.len() == 1 {
-            5  // cmd + addr(2) + value(2)
-        } else {
-            let byte_count = (data.len() + 7) / 8;
-            6 + byte_count  // cmd + addr(2) + count(2) + byte_count(1) + data
-        };
+    /// Convert a caller value to a register word, with the same 0..=65535
+    /// range the transports' `set` and `create_write_request_using` accept
+    fn register_word(value: i32, index: usize) -> Result<u16, ModbusUnitError> {
+        u16::try_from(value).map_err(|_| ModbusUnitError::ValueOverflow(value, index))
+    }
 
-        let mut result: Vec<u8> = Vec::with_capacity(capacity);
-        result.push(cmd);
+    /// Build a Read/Write Multiple Registers (0x17) PDU that reads `read_qty`
+    /// registers at `read_addr` and writes `write_data` at `start_addr`.
+    /// Values must fit an unsigned 16-bit register.
+    pub fn get_read_write_request(&self, read_addr: u16, read_qty: u16, write_data: &[i32]) -> Result<Vec<u8>, ModbusUnitError> {
+        if !matches!(self.register_type, RegisterType::HoldingRegister) {
+            return Err(ModbusUnitError::InvalidRegisterTypeForWriteCommand(self.register_type));
+        }
+        if !(1..=125).contains(&read_qty) {
+            return Err(ModbusUnitError::InvalidReadCount { count: read_qty, max: 125 });
+        }
+        if write_data.is_empty() || write_data.len() > 121 {
+            return Err(ModbusUnitError::DataLengthMismatch { expected: 121, actual: write_data.len() });
+        }
+
+        let values = write_data
+            .iter()
+            .enumerate()
+            .map(|(i, &val)| Self::register_word(val, i))
+            .collect::<Result<Vec<u16>, _>>()?;
+
+        let mut msg = Vec::with_capacity(10 + values.len() * 2);
+        msg.push(function_code::READ_WRITE_MULTIPLE_REGISTERS);
+        msg.extend(read_addr.to_be_bytes());
+        msg.extend(read_qty.to_be_bytes());
+        msg.extend(self.start_addr.to_be_bytes());
+        msg.extend((values.len() as u16).to_be_bytes());
+        msg.push((values.len() * 2) as u8);
+        for value in values {
+            msg.extend(value.to_be_bytes());
+        }
+        self.last_read_write.set(Some((read_addr, read_qty)));
+        Ok(msg)
+    }
+
+    /// Parse a Read/Write Multiple Registers (0x17) response using the read
+    /// quantity of the last `get_read_write_request`, which is consumed.
+    /// Values are stored when the read block starts at `start_addr`.
+    pub fn parse_read_write_response(&self, pdu: &[u8]) -> Result<Vec<u16>, ModbusUnitError> {
+        let (read_addr, read_qty) = self.last_read_write
+            .take()
+            .ok_or(ModbusUnitError::NoPendingRequest(function_code::READ_WRITE_MULTIPLE_REGISTERS))?;
+        Self::check_function_code(pdu, function_code::READ_WRITE_MULTIPLE_REGISTERS)?;
+
+        let values = self.parse_holding_registers(pdu, read_qty)?;
+        if read_addr == self.start_addr {
+            let stored = values.len().min(self.length as usize);
+            self.store_values(&values[..stored]);
+        }
+        Ok(values)
+    }
+
+    /// Check the acknowledgement to one PDU from `get_write_requests`.
+    /// Sparse single writes echo their own address rather than `start_addr`.
+    pub(crate) fn check_write_ack(&self, response: &[u8], request: &[u8]) -> Result<(), ModbusUnitError> {
+        if !self.sparse_writes {
+            return self.parse_write_response(response);
+        }
+        if request.len() < 3 {
+            return Err(ModbusUnitError::InvalidRequestLength);
+        }
+
+        Self::check_function_code(response, request[0])?;
+        if response.len() < 5 {
+            return Err(ModbusUnitError::InvalidResponseLength);
+        }
+        let expected = ((request[1] as u16) << 8) | (request[2] as u16);
+        let received = ((response[1] as u16) << 8) | (response[2] as u16);
+        if received != expected {
+            return Err(ModbusUnitError::AddressEchoMismatch { expected, received });
+        }
+        self.confirm_write(received, 1);
+        Ok(())
+    }
+
+    fn get_single_write_body(&self, address: u16, value: u16, cmd: u8) -> Result<Vec<u8>, ModbusUnitError> {
+        let value = match self.register_type {
+            RegisterType::CoilRegister => match value {
+                0 => 0x0000,
+                1 => self.coil_true_value,
+                _ => return Err(ModbusUnitError::InvalidCoilValue(value as i32, (address - self.start_addr) as usize)),
+            },
+            RegisterType::HoldingRegister => value,
+            _ => return Err(ModbusUnitError::InvalidRegisterTypeForWriteCommand(self.register_type)),
+        };
+        Ok(vec![cmd, (address >> 8) as u8, address as u8, (value >> 8) as u8, value as u8])
+    }
+
+    fn get_write_command(&self, length: usize) -> Result<u8, ModbusUnitError> {
+        if self.is_multi_write(length) {
+            self.get_multi_write_command()
+        } else {
+            self.get_single_write_command()
+        }
+    }
+
+    /// More than one value, or any with `force_multi_write`, uses the multi-write layout
+    fn is_multi_write(&self, length: usize) -> bool {
+        self.force_multi_write || length > 1
+    }
+
+    fn get_single_write_command(&self) -> Result<u8, ModbusUnitError> {
+        let cmd = match self.write_cmd {
+            Some(cmd) => cmd as u8,
+            None => match self.register_type {
+                RegisterType::CoilRegister => function_code::WRITE_SINGLE_COIL,
+                RegisterType::HoldingRegister => function_code::WRITE_SINGLE_REGISTER,
+                _ => return Err(ModbusUnitError::InvalidRegisterTypeForWriteCommand(self.register_type))
+            }
+        };
+        Ok(cmd)
+    }
+
+    fn get_multi_write_command(&self) -> Result<u8, ModbusUnitError> {
+        let cmd = match self.multi_write_cmd {
+            Some(cmd) => cmd as u8,
+            None => match self.register_type {
+                RegisterType::CoilRegister => function_code::WRITE_MULTIPLE_COILS,
+                RegisterType::HoldingRegister => function_code::WRITE_MULTIPLE_REGISTERS,
+                _ => return Err(ModbusUnitError::InvalidRegisterTypeForWriteCommand(self.register_type))
+            }
+        };
+        Ok(cmd)
+    }
+
+    fn get_for_body_for_holding_write(&self, data: &[u16], cmd: u8, multi: bool) -> Result<Vec<u8>, ModbusUnitError> {
+        let request_len = 3 + { if multi {3 + data.len() * 2} else {2} };
+        let mut result: Vec<u8> = Vec::with_capacity(request_len);
+        result.push(cmd);
+        result.push((self.start_addr >> 8) as u8);
+        result.push(self.start_addr as u8);
+
+        if multi {
+            result.push((data.len() >> 8) as u8);
+            result.push(data.len() as u8);
+            result.push((data.len() * 2) as u8);  // ← ДОДАНО ByteCount!
+        }
+
+        for &item in data.iter() {
+            result.push((item >> 8) as u8);
+            result.push(item as u8);
+        }
+
+        if multi {
+            Self::check_multi_write_byte_count(&result, data.len() * 2)?;
+        }
+        Ok(result)
+    }
+
+    fn get_for_body_for_coils_write(&self, data: &[u16], cmd: u8, multi: bool) -> Result<Vec<u8>, ModbusUnitError> {
+        // Validate: all values must be 0 or 1
+        for (i, &val) in data.iter().enumerate() {
+            if val != 0 && val != 1 {
+                return Err(ModbusUnitError::InvalidCoilValue(val as i32, i));
+            }
+        }
+
+        let capacity = if !multi {
+            5  // cmd + addr(2) + value(2)
+        } else {
+            let byte_count = data.len().div_ceil(8);
+            6 + byte_count  // cmd + addr(2) + count(2) + byte_count(1) + data
+        };
+
+        let mut result: Vec<u8> = Vec::with_capacity(capacity);
+        result.push(cmd);
         result.push((self.start_addr >> 8) as u8);
         result.push(self.start_addr as u8);
 
-        if data.len() == 1 {
-            // Single coil: 0xFF00 for true, 0x0000 for false
-            let val = if data[0] != 0 { 0xFF } else { 0x00 };
-            result.push(val);
-            result.push(0x00);
-        } else {
-            // Multiple coils
-            result.push((data.len() >> 8) as u8);
-            result.push(data.len() as u8);
+        if !multi {
+            // Single coil: coil_true_value (0xFF00 by default) for true, 0x0000 for false
+            let val: u16 = if data[0] != 0 { self.coil_true_value } else { 0x0000 };
+            result.push((val >> 8) as u8);
+            result.push(val as u8);
+        } else {
+            // Multiple coils
+            result.push((data.len() >> 8) as u8);
+            result.push(data.len() as u8);
+
+            // Calculate byte count
+            let byte_count = data.len().div_ceil(8);
+            result.push(byte_count as u8);
+
+            result.extend(self.pack_coils(data));
+            Self::check_multi_write_byte_count(&result, byte_count)?;
+        }
+        Ok(result)
+    }
+
+    /// Pack 0/1 values into bytes using the configured coil bit order
+    fn pack_coils(&self, data: &[u16]) -> Vec<u8> {
+        let mut bytes = vec![0u8; data.len().div_ceil(8)];
+        for (i, &bit) in data.iter().enumerate() {
+            if bit != 0 {
+                bytes[i / 8] |= 1 << self.coil_bit_order.bit_index(i);
+            }
+        }
+        bytes
+    }
+
+    /// Self-check for multi-write PDUs: the ByteCount field at offset 5 must
+    /// match the quantity and the number of data bytes actually encoded.
+    fn check_multi_write_byte_count(pdu: &[u8], expected: usize) -> Result<(), ModbusUnitError> {
+        let byte_count = pdu.get(5).copied().unwrap_or(0) as usize;
+        if byte_count != expected {
+            return Err(ModbusUnitError::EncodedByteCountMismatch { expected, actual: byte_count });
+        }
+        let data_len = pdu.len().saturating_sub(6);
+        if data_len != expected {
+            return Err(ModbusUnitError::EncodedByteCountMismatch { expected, actual: data_len });
+        }
+        Ok(())
+    }
+
+    pub fn parse_response(&self, pdu: &[u8]) -> Result<(), ModbusUnitError> {
+        let values = self.decode_response(pdu, self.length)?;
+        self.store_values(&values);
+        Ok(())
+    }
+
+    /// Parse a register response as consecutive IEEE-754 floats, two registers
+    /// each, combined per `word_order` and `byte_order`
+    pub fn parse_as_f32(&self, pdu: &[u8]) -> Result<Vec<f32>, ModbusUnitError> {
+        Ok(self.parse_register_pairs(pdu)?
+            .into_iter()
+            .map(|pair| registers_to_f32(pair, self.word_order))
+            .collect())
+    }
+
+    /// Parse a register response as consecutive u32 values, two registers
+    /// each, combined per `word_order` and `byte_order`
+    pub fn parse_as_u32(&self, pdu: &[u8]) -> Result<Vec<u32>, ModbusUnitError> {
+        Ok(self.parse_register_pairs(pdu)?
+            .into_iter()
+            .map(|pair| registers_to_u32(pair, self.word_order))
+            .collect())
+    }
+
+    /// Parse a register response as consecutive i32 values, two registers
+    /// each, combined per `word_order` and `byte_order`
+    pub fn parse_as_i32(&self, pdu: &[u8]) -> Result<Vec<i32>, ModbusUnitError> {
+        Ok(self.parse_register_pairs(pdu)?
+            .into_iter()
+            .map(|pair| registers_to_u32(pair, self.word_order) as i32)
+            .collect())
+    }
+
+    /// Build a write request PDU for `values` as IEEE-754 floats, two registers
+    /// each, split per `word_order` and `byte_order`. `values` must cover the whole unit.
+    pub fn get_f32_write_request(&self, values: &[f32]) -> Result<Vec<u8>, ModbusUnitError> {
+        self.check_value_width(2)?;
+        self.get_groups_write_request(values.iter().map(|&value| f32_to_registers(value, self.word_order)))
+    }
+
+    /// Build a write request PDU for `values` as u32, two registers each,
+    /// split per `word_order` and `byte_order`. `values` must cover the whole unit.
+    pub fn get_u32_write_request(&self, values: &[u32]) -> Result<Vec<u8>, ModbusUnitError> {
+        self.check_value_width(2)?;
+        self.get_groups_write_request(values.iter().map(|&value| u32_to_registers(value, self.word_order)))
+    }
+
+    /// Build a write request PDU for `values` as i32, two registers each,
+    /// split per `word_order` and `byte_order`. `values` must cover the whole unit.
+    pub fn get_i32_write_request(&self, values: &[i32]) -> Result<Vec<u8>, ModbusUnitError> {
+        self.check_value_width(2)?;
+        self.get_groups_write_request(values.iter().map(|&value| u32_to_registers(value as u32, self.word_order)))
+    }
+
+    /// Parse a register response as text packed two bytes per register, high
+    /// byte first. Trailing NUL and pad bytes are trimmed; invalid UTF-8 is
+    /// replaced rather than rejected.
+    pub fn parse_as_string(&self, pdu: &[u8]) -> Result<String, ModbusUnitError> {
+        if self.is_bit_data() {
+            return Err(ModbusUnitError::NotRegisterData(self.register_type));
+        }
+        let values = self.decode_response(pdu, self.length)?;
+        self.store_values(&values);
+
+        let bytes: Vec<u8> = values.iter().flat_map(|value| value.to_be_bytes()).collect();
+        let end = bytes
+            .iter()
+            .rposition(|&byte| byte != 0x00 && byte != self.pad_byte)
+            .map_or(0, |last| last + 1);
+        Ok(String::from_utf8_lossy(&bytes[..end]).into_owned())
+    }
+
+    /// Parse a register response as consecutive u64 values, four registers
+    /// each, combined per `word_order` and `byte_order` (see `u64_to_registers`)
+    pub fn parse_as_u64(&self, pdu: &[u8]) -> Result<Vec<u64>, ModbusUnitError> {
+        Ok(self.parse_register_quads(pdu)?
+            .into_iter()
+            .map(|quad| registers_to_u64(quad, self.word_order))
+            .collect())
+    }
+
+    /// Parse a register response as consecutive i64 values, four registers
+    /// each, combined per `word_order` and `byte_order`
+    pub fn parse_as_i64(&self, pdu: &[u8]) -> Result<Vec<i64>, ModbusUnitError> {
+        Ok(self.parse_register_quads(pdu)?
+            .into_iter()
+            .map(|quad| registers_to_u64(quad, self.word_order) as i64)
+            .collect())
+    }
+
+    /// Parse a register response as consecutive IEEE-754 doubles, four
+    /// registers each, combined per `word_order` and `byte_order`
+    pub fn parse_as_f64(&self, pdu: &[u8]) -> Result<Vec<f64>, ModbusUnitError> {
+        Ok(self.parse_register_quads(pdu)?
+            .into_iter()
+            .map(|quad| registers_to_f64(quad, self.word_order))
+            .collect())
+    }
+
+    /// Build a write request PDU for `values` as u64, four registers each,
+    /// split per `word_order` and `byte_order`. `values` must cover the whole unit.
+    pub fn get_u64_write_request(&self, values: &[u64]) -> Result<Vec<u8>, ModbusUnitError> {
+        self.check_value_width(4)?;
+        self.get_groups_write_request(values.iter().map(|&value| u64_to_registers(value, self.word_order)))
+    }
+
+    /// Build a write request PDU for `values` as i64, four registers each,
+    /// split per `word_order` and `byte_order`. `values` must cover the whole unit.
+    pub fn get_i64_write_request(&self, values: &[i64]) -> Result<Vec<u8>, ModbusUnitError> {
+        self.check_value_width(4)?;
+        self.get_groups_write_request(values.iter().map(|&value| u64_to_registers(value as u64, self.word_order)))
+    }
+
+    /// Build a write request PDU for `values` as IEEE-754 doubles, four registers
+    /// each, split per `word_order` and `byte_order`. `values` must cover the whole unit.
+    pub fn get_f64_write_request(&self, values: &[f64]) -> Result<Vec<u8>, ModbusUnitError> {
+        self.check_value_width(4)?;
+        self.get_groups_write_request(values.iter().map(|&value| f64_to_registers(value, self.word_order)))
+    }
+
+    /// Decode and store a register response, grouped into 32-bit register pairs
+    fn parse_register_pairs(&self, pdu: &[u8]) -> Result<Vec<[u16; 2]>, ModbusUnitError> {
+        self.check_value_width(2)?;
+        self.parse_register_groups(pdu)
+    }
+
+    /// Decode and store a register response, grouped into 64-bit register quads
+    fn parse_register_quads(&self, pdu: &[u8]) -> Result<Vec<[u16; 4]>, ModbusUnitError> {
+        self.check_value_width(4)?;
+        self.parse_register_groups(pdu)
+    }
+
+    fn check_value_width(&self, registers: u16) -> Result<(), ModbusUnitError> {
+        if !self.length.is_multiple_of(registers) {
+            return Err(ModbusUnitError::LengthNotMultiple { length: self.length, registers });
+        }
+        Ok(())
+    }
+
+    fn parse_register_groups<const N: usize>(&self, pdu: &[u8]) -> Result<Vec<[u16; N]>, ModbusUnitError> {
+        let values = self.decode_response(pdu, self.length)?;
+        self.store_values(&values);
+
+        Ok(values
+            .chunks_exact(N)
+            .map(|group| std::array::from_fn(|i| self.apply_byte_order(group[i])))
+            .collect())
+    }
+
+    fn get_groups_write_request<const N: usize>(&self, groups: impl Iterator<Item = [u16; N]>) -> Result<Vec<u8>, ModbusUnitError> {
+        let registers: Vec<u16> = groups
+            .flatten()
+            .map(|register| self.apply_byte_order(register))
+            .collect();
+        self.get_write_request_using(&registers, WriteMode::Auto)
+    }
+
+    /// Swap the bytes of a register when `byte_order` is little-endian
+    fn apply_byte_order(&self, register: u16) -> u16 {
+        match self.byte_order {
+            Endianness::Big => register,
+            Endianness::Little => register.swap_bytes(),
+        }
+    }
+
+    /// Parse a read response, store it and return the values, see `last_values`
+    pub fn parse_response_cached(&self, pdu: &[u8]) -> Result<Vec<u16>, ModbusUnitError> {
+        let values = self.decode_response(pdu, self.length)?;
+        self.store_values(&values);
+        Ok(values)
+    }
+
+    /// Parse a coil or discrete input response and report every transition
+    /// against the values stored by the previous poll (see `last_values`).
+    /// The first poll only establishes the baseline and returns no edges.
+    pub fn parse_coils_edges(&self, pdu: &[u8]) -> Result<Vec<Edge>, ModbusUnitError> {
+        if !self.is_bit_data() {
+            return Err(ModbusUnitError::NotBitData(self.register_type));
+        }
+        let previous = self.last_values();
+        let current = self.parse_response_cached(pdu)?;
+
+        let Some(previous) = previous else {
+            return Ok(Vec::new());
+        };
+        Ok(previous
+            .iter()
+            .zip(&current)
+            .enumerate()
+            .filter(|(_, (before, after))| before != after)
+            .map(|(index, (_, &after))| Edge { index, rising: after != 0 })
+            .collect())
+    }
+
+    /// Values stored by the last successful parse, `None` before the first
+    pub fn last_values(&self) -> Option<Vec<u16>> {
+        self.last_updated.get().map(|_| self.values())
+    }
+
+    /// When a successful parse last stored values on the unit
+    pub fn last_updated(&self) -> Option<Instant> {
+        self.last_updated.get()
+    }
+
+    /// Parse a read response and return the values with a stable 64-bit
+    /// FNV-1a hash of their big-endian bytes, for cheap change detection
+    pub fn parse_response_with_hash(&self, pdu: &[u8]) -> Result<(Vec<u16>, u64), ModbusUnitError> {
+        let values = self.decode_response(pdu, self.length)?;
+        self.store_values(&values);
+        let hash = values
+            .iter()
+            .flat_map(|v| v.to_be_bytes())
+            .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+                (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+            });
+        Ok((values, hash))
+    }
+
+    /// Parse a read response into per-register address, raw and scaled values
+    pub fn parse_response_full(&self, pdu: &[u8]) -> Result<Vec<RegisterValue>, ModbusUnitError> {
+        let values = self.decode_response(pdu, self.length)?;
+        self.store_values(&values);
+        Ok(values
+            .into_iter()
+            .enumerate()
+            .map(|(i, raw)| RegisterValue {
+                address: self.start_addr + i as u16,
+                raw,
+                scaled: self.scaling.map(|(scale, offset)| raw as f32 * scale + offset),
+            })
+            .collect())
+    }
+
+    /// Parse a read response, replacing positions listed in `skip_indices` with `None`
+    pub fn parse_response_filtered(&self, pdu: &[u8]) -> Result<Vec<Option<u16>>, ModbusUnitError> {
+        let values = self.decode_response(pdu, self.length)?;
+        self.store_values(&values);
+        Ok(values
+            .into_iter()
+            .enumerate()
+            .map(|(i, value)| (!self.skip_indices.contains(&i)).then_some(value))
+            .collect())
+    }
+
+    /// Parse two responses to the same read and store the values only if both
+    /// agree, guarding safety-critical setpoints against transient corruption
+    pub fn parse_response_verified(&self, first_pdu: &[u8], second_pdu: &[u8]) -> Result<Vec<u16>, ModbusUnitError> {
+        let first = self.decode_response(first_pdu, self.length)?;
+        let second = self.decode_response(second_pdu, self.length)?;
+        if first != second {
+            return Err(ModbusUnitError::VerificationMismatch { first, second });
+        }
+        self.store_values(&first);
+        Ok(first)
+    }
+
+    /// Parse a read response, returning the decoded values together with the
+    /// exact data bytes from the PDU for archival or later re-interpretation
+    pub fn parse_response_raw(&self, pdu: &[u8]) -> Result<(Vec<u16>, Vec<u8>), ModbusUnitError> {
+        let values = self.decode_response(pdu, self.length)?;
+        let raw = self.response_data(pdu, self.length)?.to_vec();
+        self.store_values(&values);
+        Ok((values, raw))
+    }
+
+    /// Parse the responses to `create_read_requests`, in order. Nothing is
+    /// stored unless every chunk parses.
+    pub fn parse_responses(&self, pdus: &[Vec<u8>]) -> Result<(), ModbusUnitError> {
+        let chunks = self.read_chunks();
+        if pdus.len() != chunks.len() {
+            return Err(ModbusUnitError::ResponseCountMismatch {
+                expected: chunks.len(),
+                actual: pdus.len(),
+            });
+        }
+
+        let (values, failure) = self.parse_responses_partial(pdus);
+        if let Some((_, err)) = failure {
+            return Err(err);
+        }
+        self.store_values(&values);
+        Ok(())
+    }
+
+    /// Decode the responses to `create_read_requests` up to the first failing
+    /// chunk. Returns the values of every chunk before it, plus the chunk index
+    /// and error where decoding stopped. Stores nothing.
+    pub fn parse_responses_partial(&self, pdus: &[Vec<u8>]) -> (Vec<u16>, Option<(usize, ModbusUnitError)>) {
+        let chunks = self.read_chunks();
+        let mut values: Vec<u16> = Vec::with_capacity(self.length as usize);
+        for (index, &(_, quantity)) in chunks.iter().enumerate() {
+            let Some(pdu) = pdus.get(index) else {
+                let err = ModbusUnitError::ResponseCountMismatch { expected: chunks.len(), actual: pdus.len() };
+                return (values, Some((index, err)));
+            };
+            match self.decode_response(pdu, quantity) {
+                Ok(chunk) => values.extend(chunk),
+                Err(err) => return (values, Some((index, err))),
+            }
+        }
+        if pdus.len() > chunks.len() {
+            let err = ModbusUnitError::ResponseCountMismatch { expected: chunks.len(), actual: pdus.len() };
+            return (values, Some((chunks.len(), err)));
+        }
+        (values, None)
+    }
+
+    /// Parse a read response and reject any value outside `[min, max]`,
+    /// e.g. 0xFFFF from a disconnected sensor
+    pub fn parse_response_checked(&self, pdu: &[u8], min: u16, max: u16) -> Result<Vec<u16>, ModbusUnitError> {
+        let values = self.decode_response(pdu, self.length)?;
+        if let Some((index, &value)) = values.iter().enumerate().find(|(_, v)| !(min..=max).contains(*v)) {
+            return Err(ModbusUnitError::ValueOutOfExpectedRange { value, index });
+        }
+        self.store_values(&values);
+        Ok(values)
+    }
+
+    /// Parse a register response into consecutive groups of `stride` registers,
+    /// e.g. interleaved `[value, quality]` pairs
+    pub fn parse_response_grouped(&self, pdu: &[u8], stride: usize) -> Result<Vec<Vec<u16>>, ModbusUnitError> {
+        let values = self.decode_response(pdu, self.length)?;
+        if stride == 0 || !values.len().is_multiple_of(stride) {
+            return Err(ModbusUnitError::InvalidStride { stride, count: values.len() });
+        }
+        self.store_values(&values);
+        Ok(values.chunks(stride).map(<[u16]>::to_vec).collect())
+    }
+
+    /// Parse a read response and map each register through `map`, falling
+    /// back to the numeric value for unmapped codes
+    pub fn parse_response_mapped(&self, pdu: &[u8], map: &HashMap<u16, String>) -> Result<Vec<String>, ModbusUnitError> {
+        let values = self.decode_response(pdu, self.length)?;
+        self.store_values(&values);
+        Ok(values
+            .iter()
+            .map(|value| map.get(value).cloned().unwrap_or_else(|| value.to_string()))
+            .collect())
+    }
+
+    /// Commissioning aid: decode a register response with both byte orders
+    /// and report which one keeps every value inside `plausible_range`
+    pub fn detect_byte_order(&self, pdu: &[u8], plausible_range: RangeInclusive<u16>) -> Result<ByteOrderGuess, ModbusUnitError> {
+        if self.is_bit_data() {
+            return Err(ModbusUnitError::NotRegisterData(self.register_type));
+        }
+        let values = self.decode_response(pdu, self.length)?;
+
+        let as_is = values.iter().all(|v| plausible_range.contains(v));
+        let swapped = values.iter().all(|v| plausible_range.contains(&v.swap_bytes()));
+        Ok(match (as_is, swapped) {
+            (true, false) => ByteOrderGuess::AsIs,
+            (false, true) => ByteOrderGuess::Swapped,
+            _ => ByteOrderGuess::Ambiguous,
+        })
+    }
+
+    /// Build the read response PDU a compliant device would send for `values`,
+    /// the inverse of `parse_response`. Coil and discrete values must be 0 or 1.
+    pub fn encode_read_response(&self, values: &[u16]) -> Result<Vec<u8>, ModbusUnitError> {
+        if values.len() != self.length as usize {
+            return Err(ModbusUnitError::DataLengthMismatch {
+                expected: self.length as usize,
+                actual: values.len(),
+            });
+        }
+        self.encode_read_values(values)
+    }
+
+    /// Read response PDU carrying `values`, laid out as this unit's parser expects
+    fn encode_read_values(&self, values: &[u16]) -> Result<Vec<u8>, ModbusUnitError> {
+        let data = if self.is_bit_data() {
+            if let Some((i, &val)) = values.iter().enumerate().find(|(_, v)| **v > 1) {
+                return Err(ModbusUnitError::InvalidCoilValue(val as i32, i));
+            }
+            self.pack_coils(values)
+        } else {
+            values.iter().flat_map(|v| v.to_be_bytes()).collect()
+        };
+
+        let mut result: Vec<u8> = Vec::with_capacity(2 + data.len());
+        result.push(self.get_read_command());
+        if self.is_bit_data() {
+            result.push(data.len() as u8);
+        } else if self.response_has_byte_count {
+            let count = self.count_unit.count_from_bytes(data.len());
+            if self.wide_byte_count {
+                result.extend((count as u16).to_be_bytes());
+            } else {
+                result.push(count as u8);
+            }
+        }
+        result.extend(data);
+        Ok(result)
+    }
+
+    /// Length of a complete read response PDU for this unit
+    pub(crate) fn expected_response_pdu_len(&self) -> usize {
+        if self.is_bit_data() {
+            return 2 + (self.length as usize).div_ceil(8);
+        }
+        let byte_count_len = match (self.response_has_byte_count, self.wide_byte_count) {
+            (false, _) => 0,
+            (true, false) => 1,
+            (true, true) => 2,
+        };
+        1 + byte_count_len + self.length as usize * 2
+    }
+
+    /// ByteCount a compliant response to a read of `quantity` values carries,
+    /// `None` when the unit is configured to accept responses without an exact ByteCount
+    pub(crate) fn expected_byte_count(&self, quantity: u16) -> Option<u8> {
+        if self.is_bit_data() {
+            return Some((quantity as usize).div_ceil(8) as u8);
+        }
+        if !self.response_has_byte_count || self.short_read_pad.is_some() || self.wide_byte_count {
+            return None;
+        }
+        Some(self.count_unit.count_from_bytes(quantity as usize * 2) as u8)
+    }
+
+    /// Happy-path response PDU a compliant server would send for `request_pdu`,
+    /// for building mock devices. The unit's read request is answered with
+    /// `values` encoded as by `encode_read_response` (0/1 for coils and discrete
+    /// inputs); single and multiple writes answer with their echo.
+    pub fn mock_response_for(&self, request_pdu: &[u8], values: &[u16]) -> Result<Vec<u8>, ModbusUnitError> {
+        if request_pdu.len() < 5 {
+            return Err(ModbusUnitError::InvalidRequestLength);
+        }
+        let request_fc = request_pdu[0];
+        let quantity = ((request_pdu[3] as usize) << 8) | (request_pdu[4] as usize);
+        let read_command = self.read_cmd.map_or(self.get_read_command(), |cmd| cmd as u8);
+
+        match request_fc {
+            fc if fc == read_command => {
+                if values.len() != quantity {
+                    return Err(ModbusUnitError::DataLengthMismatch {
+                        expected: quantity,
+                        actual: values.len(),
+                    });
+                }
+                self.encode_read_values(values)
+            }
+            function_code::WRITE_SINGLE_COIL | function_code::WRITE_SINGLE_REGISTER
+            | function_code::WRITE_MULTIPLE_COILS | function_code::WRITE_MULTIPLE_REGISTERS => {
+                Ok(request_pdu[..5].to_vec())
+            }
+            _ => Err(ModbusUnitError::UnsupportedFunctionCode(request_fc)),
+        }
+    }
+
+    /// Values stored by the last successful parse
+    pub(crate) fn values(&self) -> Vec<u16> {
+        self.read_vec.borrow().clone()
+    }
+
+    pub(crate) fn store_values(&self, values: &[u16]) {
+        self.read_vec.borrow_mut()[..values.len()].copy_from_slice(values);
+        self.last_updated.set(Some(Instant::now()));
+    }
+
+    /// Parse a write acknowledgement. Expects the write function code for this
+    /// unit and never accepts a read response (see `parse_response`).
+    pub fn parse_write_response(&self, pdu: &[u8]) -> Result<(), ModbusUnitError> {
+        if self.is_multi_write(self.length as usize) {
+            return self.parse_multi_write_response(pdu).map(|_| ());
+        }
+
+        Self::check_function_code(pdu, self.get_single_write_command()?)?;
+        self.check_echoed_address(pdu)?;
+        self.confirm_write(self.start_addr, 1);
+        Ok(())
+    }
+
+    /// Parse a write-multiple (0x0F/0x10) acknowledgement, checking the echoed
+    /// address and that the echoed quantity equals the unit length.
+    /// Returns the echoed quantity.
+    pub fn parse_multi_write_response(&self, pdu: &[u8]) -> Result<u16, ModbusUnitError> {
+        Self::check_function_code(pdu, self.get_multi_write_command()?)?;
+        self.check_echoed_address(pdu)?;
+
+        let quantity = ((pdu[3] as u16) << 8) | (pdu[4] as u16);
+        if quantity != self.length {
+            return Err(ModbusUnitError::QuantityEchoMismatch {
+                expected: self.length,
+                received: quantity,
+            });
+        }
+        self.confirm_write(self.start_addr, quantity);
+        Ok(quantity)
+    }
+
+    /// Compare a read-back response of the written range against the staged
+    /// write values, completing a write-then-read verification round trip
+    pub fn verify_write(&self, readback_pdu: &[u8]) -> Result<(), ModbusUnitError> {
+        let expected = self.write_vec
+            .borrow()
+            .iter()
+            .enumerate()
+            .map(|(i, opt)| {
+                opt.ok_or(ModbusUnitError::WriteValueNotSet {
+                    index: i,
+                    address: self.start_addr + i as u16,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let actual = self.decode_response(readback_pdu, self.length)?;
+        self.store_values(&actual);
+        if actual != expected {
+            return Err(ModbusUnitError::WriteVerificationFailed { expected, actual });
+        }
+        Ok(())
+    }
+
+    /// Parse a Write Single Coil (0x05) echo, returning the echoed coil state
+    pub fn parse_single_coil_write_response(&self, pdu: &[u8]) -> Result<bool, ModbusUnitError> {
+        if !matches!(self.register_type, RegisterType::CoilRegister) {
+            return Err(ModbusUnitError::InvalidRegisterTypeForWriteCommand(self.register_type));
+        }
+        Self::check_function_code(pdu, self.get_single_write_command()?)?;
+        self.check_echoed_address(pdu)?;
+
+        let state = match ((pdu[3] as u16) << 8) | (pdu[4] as u16) {
+            value if value == self.coil_true_value => true,
+            0x0000 => false,
+            value => return Err(ModbusUnitError::InvalidCoilEchoValue(value)),
+        };
+        self.confirm_write(self.start_addr, 1);
+        Ok(state)
+    }
+
+    fn confirm_write(&self, address: u16, quantity: u16) {
+        if let Some(callback) = &self.on_write_confirmed {
+            callback(address, quantity);
+        }
+    }
+
+    /// Check a write echo is complete and carries the unit's start address
+    fn check_echoed_address(&self, pdu: &[u8]) -> Result<(), ModbusUnitError> {
+        if pdu.len() < 5 {
+            return Err(ModbusUnitError::InvalidResponseLength);
+        }
+
+        let address = ((pdu[1] as u16) << 8) | (pdu[2] as u16);
+        if address != self.start_addr {
+            return Err(ModbusUnitError::AddressEchoMismatch {
+                expected: self.start_addr,
+                received: address,
+            });
+        }
+        Ok(())
+    }
+
+    /// Validate a read response PDU and decode `quantity` values without storing them
+    pub(crate) fn decode_response(&self, pdu: &[u8], quantity: u16) -> Result<Vec<u16>, ModbusUnitError> {
+        Self::check_function_code(pdu, self.get_read_command())?;
+
+        // Parse based on register type
+        if self.is_bit_data() {
+            return self.parse_coils(pdu, quantity);
+        }
+
+        let values = self.parse_holding_registers(pdu, quantity)?;
+        if self.fault_on_uniform
+            && let Some(&pattern) = values.first()
+            && (pattern == 0x0000 || pattern == 0xFFFF)
+            && values.iter().all(|&v| v == pattern)
+        {
+            return Err(ModbusUnitError::SuspectedCommFault { pattern });
+        }
+        Ok(values)
+    }
 
-            // Calculate byte count
-            let byte_count = (data.len() + 7) / 8;
-            result.push(byte_count as u8);
+    /// Validate a read response PDU and return its raw data region
+    fn response_data<'a>(&self, pdu: &'a [u8], quantity: u16) -> Result<&'a [u8], ModbusUnitError> {
+        Self::check_function_code(pdu, self.get_read_command())?;
 
-            // Pack bits into bytes
-            let mut bytes = vec![0u8; byte_count];
-            for (i, &bit) in data.iter().enumerate() {
-                if bit != 0 {
-                    bytes[i / 8] |= 1 << (i % 8);
-                }
-            }
-            result.extend(bytes);
+        if self.is_bit_data() {
+            Self::coil_data(pdu, quantity)
+        } else {
+            self.register_data(pdu, quantity)
         }
-        Ok(result)
     }
-    pub fn parse_response(&self, pdu: &[u8]) -> Result<(), ModbusUnitError> {
+
+    /// Reject empty PDUs, exception responses and unexpected function codes
+    fn check_function_code(pdu: &[u8], expected_fc: u8) -> Result<(), ModbusUnitError> {
         if pdu.is_empty() {
             return Err(ModbusUnitError::EmptyResponse);
         }
 
-        let function_code = pdu[0];
+        let received_fc = pdu[0];
 
         // Check for Modbus exception (function code | 0x80)
-        if (function_code & 0x80) != 0 {
+        if (received_fc & function_code::EXCEPTION_OFFSET) != 0 {
             let exception_code = if pdu.len() > 1 { pdu[1] } else { 0 };
-            return Err(ModbusUnitError::ModbusException(function_code, exception_code));
+            return Err(ModbusUnitError::ModbusException(received_fc, exception_code));
         }
 
         // Verify function code matches expected
-        let expected_fc = self.get_read_command();
-        if function_code != expected_fc {
-            return Err(ModbusUnitError::UnexpectedFunctionCode(expected_fc, function_code));
+        if received_fc != expected_fc {
+            return Err(ModbusUnitError::UnexpectedFunctionCode(expected_fc, received_fc));
         }
+        Ok(())
+    }
 
-        // Parse based on register type
-        match self.register_type {
-            RegisterType::HoldingRegister | RegisterType::InputRegister => {
-                self.parse_holding_registers(pdu)
-            }
-            RegisterType::CoilRegister | RegisterType::DiscreteRegister => {
-                self.parse_coils(pdu)
+    fn parse_holding_registers(&self, pdu: &[u8], quantity: u16) -> Result<Vec<u16>, ModbusUnitError> {
+        let data = self.register_data(pdu, quantity)?;
+
+        let mut result: Vec<u16> = Vec::with_capacity(quantity as usize);
+        for chunk in data.chunks_exact(2) {
+            let value = ((chunk[0] as u16) << 8) | (chunk[1] as u16);
+            result.push(value);
+        }
+
+        // Fill registers missing from a short read with the configured sentinel
+        if let Some(sentinel) = self.short_read_pad {
+            result.resize(quantity as usize, sentinel);
+        }
+
+        Ok(result)
+    }
+
+    /// Validate the ByteCount of a register response and return the data bytes
+    fn register_data<'a>(&self, pdu: &'a [u8], quantity: u16) -> Result<&'a [u8], ModbusUnitError> {
+        if pdu.len() < 2 {
+            return Err(ModbusUnitError::InvalidResponseLength);
+        }
+
+        let expected_bytes = quantity as usize * 2;
+        let padding = self.short_read_pad.is_some();
+        let (data_offset, data_bytes) = if self.response_has_byte_count {
+            let (data_offset, raw_count) = if self.wide_byte_count {
+                if pdu.len() < 3 {
+                    return Err(ModbusUnitError::InvalidResponseLength);
+                }
+                (3, ((pdu[1] as usize) << 8) | (pdu[2] as usize))
+            } else {
+                (2, pdu[1] as usize)
+            };
+            let byte_count = self.count_unit.bytes_from_count(raw_count);
+            let short_read = padding && byte_count < expected_bytes && byte_count.is_multiple_of(2);
+            if byte_count != expected_bytes && !short_read {
+                return Err(ModbusUnitError::ByteCountMismatch {
+                    expected: self.count_unit.count_from_bytes(expected_bytes),
+                    received: raw_count,
+                    length: quantity,
+                });
             }
+            (data_offset, byte_count)
+        } else if padding {
+            (1, ((pdu.len() - 1) & !1).min(expected_bytes))
+        } else {
+            (1, expected_bytes)
+        };
+
+        if pdu.len() < data_offset + data_bytes {
+            return Err(ModbusUnitError::TruncatedData {
+                need: data_bytes,
+                have: pdu.len() - data_offset,
+            });
+        }
+        Ok(&pdu[data_offset..data_offset + data_bytes])
+    }
+
+    fn parse_coils(&self, pdu: &[u8], quantity: u16) -> Result<Vec<u16>, ModbusUnitError> {
+        let data = Self::coil_data(pdu, quantity)?;
+
+        let mut result = Vec::with_capacity(quantity as usize);
+        for i in 0..quantity as usize {
+            let byte_idx = i / 8;
+            let bit_idx = self.coil_bit_order.bit_index(i);
+            let bit_value = (data[byte_idx] >> bit_idx) & 0x01;
+            result.push(bit_value as u16);
         }
+        Ok(result)
     }
 
-    fn parse_holding_registers(&self, pdu: &[u8]) -> Result<(), ModbusUnitError> {
+    /// Validate the ByteCount of a coil response and return the packed data bytes
+    fn coil_data(pdu: &[u8], quantity: u16) -> Result<&[u8], ModbusUnitError> {
         if pdu.len() < 2 {
             return Err(ModbusUnitError::InvalidResponseLength);
         }
 
         let byte_count = pdu[1] as usize;
-        let expected_bytes = self.length as usize * 2;
+        let expected_bytes = (quantity as usize).div_ceil(8);
+
+        if byte_count != expected_bytes {
+            return Err(ModbusUnitError::ByteCountMismatch {
+                expected: expected_bytes,
+                received: byte_count,
+                length: quantity,
+            });
+        }
+        if pdu.len() < 2 + byte_count {
+            return Err(ModbusUnitError::TruncatedData {
+                need: byte_count,
+                have: pdu.len() - 2,
+            });
+        }
+        Ok(&pdu[2..2 + byte_count])
+    }
 
-        if byte_count != expected_bytes || pdu.len() < 2 + byte_count {
-            return Err(ModbusUnitError::InvalidResponseLength);
+    /// Parse a coil or discrete input response into its bits plus the active
+    /// count and the first active index
+    pub fn parse_discrete_inputs_summary(&self, pdu: &[u8]) -> Result<DiscreteSummary, ModbusUnitError> {
+        if !self.is_bit_data() {
+            return Err(ModbusUnitError::NotBitData(self.register_type));
+        }
+        let values = self.decode_response(pdu, self.length)?;
+        self.store_values(&values);
+
+        let bits: Vec<bool> = values.iter().map(|&v| v != 0).collect();
+        Ok(DiscreteSummary {
+            active_count: bits.iter().filter(|&&bit| bit).count(),
+            first_active: bits.iter().position(|&bit| bit),
+            bits,
+        })
+    }
+
+    /// Count set coils in a read response without decoding every bit
+    pub fn parse_coils_popcount(&self, pdu: &[u8]) -> Result<usize, ModbusUnitError> {
+        if !self.is_bit_data() {
+            return Err(ModbusUnitError::NotBitData(self.register_type));
+        }
+        Self::check_function_code(pdu, self.get_read_command())?;
+        let data = Self::coil_data(pdu, self.length)?;
+
+        let mut count = 0;
+        for (i, &byte) in data.iter().enumerate() {
+            // Ignore padding bits in the last byte
+            let bits = (self.length as usize - i * 8).min(8);
+            count += (byte & self.coil_byte_mask(bits)).count_ones() as usize;
+        }
+        Ok(count)
+    }
+
+    /// Mask selecting the first `bits` coils of a packed byte
+    fn coil_byte_mask(&self, bits: usize) -> u8 {
+        match self.coil_bit_order {
+            BitOrder::LsbFirst => (0xFFu16 >> (8 - bits)) as u8,
+            BitOrder::MsbFirst => (0xFFu16 << (8 - bits)) as u8,
+        }
+    }
+
+    /// Build a coil write PDU straight from packed bytes (in the configured
+    /// coil bit order). `bit_count` must equal the unit length and padding
+    /// bits in the last byte must be zero.
+    pub fn create_coil_write_from_packed(&self, bytes: &[u8], bit_count: usize) -> Result<Vec<u8>, ModbusUnitError> {
+        if !matches!(self.register_type, RegisterType::CoilRegister) {
+            return Err(ModbusUnitError::InvalidRegisterTypeForWriteCommand(self.register_type));
+        }
+        if bit_count == 0 {
+            return Err(ModbusUnitError::NoWriteValuesSet);
+        }
+        if bit_count != self.length as usize {
+            return Err(ModbusUnitError::DataLengthMismatch {
+                expected: self.length as usize,
+                actual: bit_count,
+            });
+        }
+        let byte_count = bit_count.div_ceil(8);
+        if bytes.len() != byte_count {
+            return Err(ModbusUnitError::DataLengthMismatch {
+                expected: byte_count,
+                actual: bytes.len(),
+            });
+        }
+        if let Some(&last) = bytes.last() {
+            let bits = bit_count - (byte_count - 1) * 8;
+            if last & !self.coil_byte_mask(bits) != 0 {
+                return Err(ModbusUnitError::PackedPaddingBitsSet);
+            }
+        }
+
+        let cmd = self.get_write_command(bit_count)?;
+        if !self.is_multi_write(bit_count) {
+            let bit = (bytes[0] >> self.coil_bit_order.bit_index(0)) & 0x01;
+            return self.get_single_write_body(self.start_addr, bit as u16, cmd);
         }
 
-        // let mut result:Vec<u16> = Vec::with_capacity(self.length as usize);
+        let mut result: Vec<u8> = Vec::with_capacity(6 + byte_count);
+        result.push(cmd);
+        result.push((self.start_addr >> 8) as u8);
+        result.push(self.start_addr as u8);
+        result.push((bit_count >> 8) as u8);
+        result.push(bit_count as u8);
+        result.push(byte_count as u8);
+        result.extend_from_slice(bytes);
+        Self::check_multi_write_byte_count(&result, byte_count)?;
+        Ok(result)
+    }
+
+    /// Encode `data` as a coil write, synthesize the matching read-coils
+    /// response and check it decodes back to the same values.
+    /// Catches bit-packing regressions for odd coil counts (7, 9, 17, ...).
+    pub fn verify_coil_roundtrip(&self, data: &[i32]) -> Result<(), ModbusUnitError> {
+        if !matches!(self.register_type, RegisterType::CoilRegister) {
+            return Err(ModbusUnitError::InvalidRegisterTypeForWriteCommand(self.register_type));
+        }
+        if data.len() != self.length as usize {
+            return Err(ModbusUnitError::DataLengthMismatch {
+                expected: self.length as usize,
+                actual: data.len(),
+            });
+        }
 
-        for i in 0..self.length as usize {
-            let offset = 2 + i * 2;
-            let value = ((pdu[offset] as u16) << 8) | (pdu[offset + 1] as u16);
-            self.read_vec.borrow_mut()[i] = value;
-            // result.push(value);
+        let mut values: Vec<u16> = Vec::with_capacity(data.len());
+        for (i, &val) in data.iter().enumerate() {
+            if val != 0 && val != 1 {
+                return Err(ModbusUnitError::InvalidCoilValue(val, i));
+            }
+            values.push(val as u16);
         }
 
+        let write_pdu = self.get_write_request_using(&values, WriteMode::Auto)?;
+
+        // Single coil carries coil_true_value/0x0000, multiple coils carry packed bytes
+        let packed = if !self.is_multi_write(values.len()) {
+            let encoded = ((write_pdu[3] as u16) << 8) | (write_pdu[4] as u16);
+            self.pack_coils(&[(encoded == self.coil_true_value) as u16])
+        } else {
+            write_pdu[6..].to_vec()
+        };
+
+        let mut response = Vec::with_capacity(2 + packed.len());
+        response.push(self.get_read_command());
+        response.push(packed.len() as u8);
+        response.extend(packed);
+
+        let decoded = self.parse_coils(&response, self.length)?;
+        if let Some(index) = (0..values.len()).find(|&i| decoded[i] != values[i]) {
+            return Err(ModbusUnitError::CoilRoundTripMismatch {
+                index,
+                written: values[index],
+                read: decoded[index],
+            });
+        }
         Ok(())
     }
 
-    fn parse_coils(&self, pdu: &[u8]) -> Result<(), ModbusUnitError> {
+    /// Build an Encapsulated Interface Transport (0x2B) request PDU for an arbitrary MEI type
+    pub fn create_mei_request(&self, mei_type: u8, data: &[u8]) -> Vec<u8> {
+        let mut msg: Vec<u8> = Vec::with_capacity(2 + data.len());
+        msg.push(function_code::ENCAPSULATED_INTERFACE_TRANSPORT);
+        msg.push(mei_type);
+        msg.extend_from_slice(data);
+        msg
+    }
+
+    /// Parse an Encapsulated Interface Transport (0x2B) response, returning the MEI type and raw payload
+    pub fn parse_mei(&self, pdu: &[u8]) -> Result<(u8, Vec<u8>), ModbusUnitError> {
+        Self::check_function_code(pdu, function_code::ENCAPSULATED_INTERFACE_TRANSPORT)?;
         if pdu.len() < 2 {
             return Err(ModbusUnitError::InvalidResponseLength);
         }
 
-        let byte_count = pdu[1] as usize;
-        let expected_bytes = (self.length as usize + 7) / 8;
+        Ok((pdu[1], pdu[2..].to_vec()))
+    }
+
+    /// Build a Read FIFO Queue (0x18) request PDU for the queue at `fifo_pointer`
+    pub fn get_read_fifo_request(&self, fifo_pointer: u16) -> Vec<u8> {
+        let mut msg = Vec::with_capacity(3);
+        msg.push(function_code::READ_FIFO_QUEUE);
+        msg.extend(fifo_pointer.to_be_bytes());
+        msg
+    }
+
+    /// Parse a Read FIFO Queue (0x18) response into the queued register values
+    pub fn parse_fifo_response(&self, pdu: &[u8]) -> Result<Vec<u16>, ModbusUnitError> {
+        Self::check_function_code(pdu, function_code::READ_FIFO_QUEUE)?;
+        if pdu.len() < 5 {
+            return Err(ModbusUnitError::InvalidResponseLength);
+        }
 
-        if byte_count != expected_bytes || pdu.len() < 2 + byte_count {
+        let byte_count = ((pdu[1] as usize) << 8) | (pdu[2] as usize);
+        let fifo_count = ((pdu[3] as u16) << 8) | (pdu[4] as u16);
+        if fifo_count > 31 {
+            return Err(ModbusUnitError::FifoCountTooLarge(fifo_count));
+        }
+        // byte_count covers the FIFO count field and the values
+        let data_bytes = fifo_count as usize * 2;
+        if byte_count != 2 + data_bytes || pdu.len() < 5 + data_bytes {
             return Err(ModbusUnitError::InvalidResponseLength);
         }
 
-        // let mut result = Vec::with_capacity(self.length as usize);
+        Ok(pdu[5..5 + data_bytes]
+            .chunks_exact(2)
+            .map(|chunk| ((chunk[0] as u16) << 8) | (chunk[1] as u16))
+            .collect())
+    }
 
-        for i in 0..self.length as usize {
-            let byte_idx = i / 8;
-            let bit_idx = i % 8;
-            let bit_value = (pdu[2 + byte_idx] >> bit_idx) & 0x01;
-            self.read_vec.borrow_mut()[i] = bit_value as u16;
-            // result.push(bit_value as u16);
+    /// Build a Read Device Identification (0x2B / 0x0E) request PDU
+    pub fn get_device_identification_request(&self, read_device_id_code: u8, object_id: u8) -> Vec<u8> {
+        vec![
+            function_code::ENCAPSULATED_INTERFACE_TRANSPORT,
+            function_code::MEI_READ_DEVICE_IDENTIFICATION,
+            read_device_id_code,
+            object_id,
+        ]
+    }
+
+    /// Parse a Read Device Identification (0x2B / 0x0E) response, walking
+    /// `number_of_objects` length-prefixed objects
+    pub fn parse_device_identification(&self, pdu: &[u8]) -> Result<DeviceIdentification, ModbusUnitError> {
+        let (mei_type, payload) = self.parse_mei(pdu)?;
+        if mei_type != function_code::MEI_READ_DEVICE_IDENTIFICATION {
+            return Err(ModbusUnitError::UnexpectedMeiType {
+                expected: function_code::MEI_READ_DEVICE_IDENTIFICATION,
+                received: mei_type,
+            });
         }
-        Ok(())
+        // read_device_id_code, conformity, more_follows, next_object_id, number_of_objects
+        if payload.len() < 5 {
+            return Err(ModbusUnitError::InvalidResponseLength);
+        }
+
+        let mut objects = Vec::with_capacity(payload[4] as usize);
+        let mut offset = 5;
+        for _ in 0..payload[4] {
+            let (id, len) = match payload.get(offset..offset + 2) {
+                Some(header) => (header[0], header[1] as usize),
+                None => return Err(ModbusUnitError::InvalidResponseLength),
+            };
+            let value = payload
+                .get(offset + 2..offset + 2 + len)
+                .ok_or(ModbusUnitError::InvalidResponseLength)?;
+            objects.push((id, value.to_vec()));
+            offset += 2 + len;
+        }
+
+        Ok(DeviceIdentification {
+            conformity_level: payload[1],
+            more_follows: payload[2] == 0xFF,
+            next_object_id: payload[3],
+            objects,
+        })
+    }
+
+    /// Build a Report Server ID (0x11) request PDU
+    pub fn get_report_server_id_request(&self) -> Vec<u8> {
+        vec![function_code::REPORT_SERVER_ID]
+    }
+
+    /// Parse a Report Server ID (0x11) response, locating the run indicator
+    /// according to `run_indicator_position`
+    pub fn parse_report_server_id(&self, pdu: &[u8]) -> Result<ServerIdReport, ModbusUnitError> {
+        Self::check_function_code(pdu, function_code::REPORT_SERVER_ID)?;
+        if pdu.len() < 2 {
+            return Err(ModbusUnitError::InvalidResponseLength);
+        }
+        let byte_count = pdu[1] as usize;
+        if pdu.len() < 2 + byte_count {
+            return Err(ModbusUnitError::InvalidResponseLength);
+        }
+        let payload = &pdu[2..2 + byte_count];
+
+        let (id_len, indicator_at) = match self.run_indicator_pos {
+            RunIndicatorPos::AfterId(id_len) => (id_len, Some(id_len)),
+            RunIndicatorPos::Last => (payload.len().saturating_sub(1), Some(payload.len().saturating_sub(1))),
+            RunIndicatorPos::None => (payload.len(), None),
+        };
+        let run_indicator = match indicator_at {
+            Some(index) => match payload.get(index) {
+                Some(0xFF) => Some(true),
+                Some(0x00) => Some(false),
+                Some(&other) => return Err(ModbusUnitError::InvalidRunIndicator(other)),
+                None => return Err(ModbusUnitError::InvalidResponseLength),
+            },
+            None => None,
+        };
+        let data_start = indicator_at.map_or(payload.len(), |index| index + 1);
+
+        Ok(ServerIdReport {
+            server_id: payload[..id_len].to_vec(),
+            run_indicator,
+            additional_data: payload[data_start..].to_vec(),
+        })
+    }
+
+    /// Build a Diagnostics (0x08) request PDU for `sub_function` with one data word
+    pub fn get_diagnostics_request(&self, sub_function: u16, data: u16) -> Vec<u8> {
+        let mut msg = Vec::with_capacity(5);
+        msg.push(function_code::DIAGNOSTICS);
+        msg.extend(sub_function.to_be_bytes());
+        msg.extend(data.to_be_bytes());
+        msg
+    }
+
+    /// Parse a Diagnostics (0x08) response, returning the echoed sub-function and data word
+    pub fn parse_diagnostics_response(&self, pdu: &[u8]) -> Result<(u16, u16), ModbusUnitError> {
+        Self::check_function_code(pdu, function_code::DIAGNOSTICS)?;
+        if pdu.len() < 5 {
+            return Err(ModbusUnitError::InvalidResponseLength);
+        }
+
+        let sub_function = ((pdu[1] as u16) << 8) | (pdu[2] as u16);
+        let data = ((pdu[3] as u16) << 8) | (pdu[4] as u16);
+        Ok((sub_function, data))
+    }
+
+    /// Check a Return Query Data loopback response echoes `sent`
+    pub fn check_loopback(&self, pdu: &[u8], sent: u16) -> Result<bool, ModbusUnitError> {
+        let (sub_function, data) = self.parse_diagnostics_response(pdu)?;
+        Ok(sub_function == function_code::DIAG_RETURN_QUERY_DATA && data == sent)
+    }
+
+    /// Build a Read Exception Status (0x07) request PDU. Serial line only;
+    /// independent of `register_type`, `start_addr` and `length`.
+    pub fn get_read_exception_status_request(&self) -> Vec<u8> {
+        vec![function_code::READ_EXCEPTION_STATUS]
+    }
+
+    /// Parse a Read Exception Status (0x07) response, returning the eight
+    /// exception status outputs packed into one byte (bit 0 first)
+    pub fn parse_exception_status(&self, pdu: &[u8]) -> Result<u8, ModbusUnitError> {
+        Self::check_function_code(pdu, function_code::READ_EXCEPTION_STATUS)?;
+        pdu.get(1).copied().ok_or(ModbusUnitError::InvalidResponseLength)
     }
+
     pub fn get(&self, index: usize) -> Result<u16, ModbusUnitError> {
         if index >= self.read_vec.borrow().len() {
             return Err(ModbusUnitError::ReadIndexOutOfRange(index));
@@ -445,4 +2296,796 @@ impl ModbusUnit {
         self.write_vec.borrow_mut()[index] = Some(value);
         Ok(())
     }
+
+    /// Stage `bytes` two per register across the whole unit, filling the
+    /// remainder with the configured pad byte
+    pub fn set_bytes(&self, bytes: &[u8]) -> Result<(), ModbusUnitError> {
+        for (i, reg) in self.padded_registers(bytes)?.into_iter().enumerate() {
+            self.set(i, reg)?;
+        }
+        Ok(())
+    }
+
+    /// Stage `text` as UTF-8, see `set_bytes`
+    pub fn set_string(&self, text: &str) -> Result<(), ModbusUnitError> {
+        self.set_bytes(text.as_bytes())
+    }
+
+    /// Build a write request PDU for `text` packed two bytes per register,
+    /// high byte first, padded to the whole unit. Staged values are untouched.
+    pub fn get_string_write_request(&self, text: &str) -> Result<Vec<u8>, ModbusUnitError> {
+        let registers = self.padded_registers(text.as_bytes())?;
+        self.get_write_request_using(&registers, WriteMode::Auto)
+    }
+
+    /// Pack `bytes` two per register across the whole unit, filling the
+    /// remainder with the configured pad byte
+    fn padded_registers(&self, bytes: &[u8]) -> Result<Vec<u16>, ModbusUnitError> {
+        if self.is_bit_data() {
+            return Err(ModbusUnitError::NotRegisterData(self.register_type));
+        }
+        let capacity = self.length as usize * 2;
+        if bytes.len() > capacity {
+            return Err(ModbusUnitError::DataLengthMismatch {
+                expected: capacity,
+                actual: bytes.len(),
+            });
+        }
+
+        let mut padded = bytes.to_vec();
+        padded.resize(capacity, self.pad_byte);
+        Ok(bytes_to_registers(&padded, self.pad_byte))
+    }
+
+    /// Reset every staged write value to unset, so a later write request
+    /// fails instead of resending stale data
+    pub fn clear_staging(&self) {
+        self.write_vec.borrow_mut().fill(None);
+    }
+
+    /// True when every index has a staged write value
+    pub fn is_staging_complete(&self) -> bool {
+        self.write_vec.borrow().iter().all(Option::is_some)
+    }
+}
+
+/// Stateless decoder for read coils / discrete inputs responses when the
+/// requested quantity is unknown (e.g. passive monitoring). Returns every bit
+/// implied by the ByteCount, LSB-first; the caller trims trailing padding.
+pub fn decode_coils_from_response(pdu: &[u8]) -> Result<Vec<bool>, ModbusUnitError> {
+    let received_fc = *pdu.first().ok_or(ModbusUnitError::EmptyResponse)?;
+    let expected_fc = match received_fc {
+        function_code::READ_COILS | function_code::READ_DISCRETE_INPUTS => received_fc,
+        _ => function_code::READ_COILS,
+    };
+    ModbusUnit::check_function_code(pdu, expected_fc)?;
+
+    if pdu.len() < 2 || pdu.len() < 2 + pdu[1] as usize {
+        return Err(ModbusUnitError::InvalidResponseLength);
+    }
+
+    Ok(pdu[2..2 + pdu[1] as usize]
+        .iter()
+        .flat_map(|byte| (0..8).map(move |bit| (byte >> bit) & 0x01 != 0))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit(register_type: RegisterType, start_addr: i32, length: i32) -> ModbusUnit {
+        let mut builder = ModbusUnit::builder();
+        builder.address(start_addr).length(length).register_type(register_type);
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn multi_write_byte_count_is_two_per_register() {
+        let unit = unit(RegisterType::HoldingRegister, 0, 3);
+        for i in 0..3 {
+            unit.set(i, i as u16).unwrap();
+        }
+        let pdu = unit.get_write_request().unwrap();
+        assert_eq!(pdu[5], 6);
+        assert!(ModbusUnit::check_multi_write_byte_count(&pdu, 6).is_ok());
+    }
+
+    #[test]
+    fn multi_write_byte_count_is_packed_for_coils() {
+        let unit = unit(RegisterType::CoilRegister, 0, 10);
+        for i in 0..10 {
+            unit.set(i, 1).unwrap();
+        }
+        let pdu = unit.get_write_request().unwrap();
+        assert_eq!(pdu[5], 2);
+        assert!(ModbusUnit::check_multi_write_byte_count(&pdu, 2).is_ok());
+    }
+
+    #[test]
+    fn multi_write_byte_count_mismatch_is_rejected() {
+        let pdu = [0x10, 0x00, 0x00, 0x00, 0x02, 0x03, 0x00, 0x01, 0x00, 0x02];
+        assert!(matches!(
+            ModbusUnit::check_multi_write_byte_count(&pdu, 4),
+            Err(ModbusUnitError::EncodedByteCountMismatch { expected: 4, actual: 3 })
+        ));
+
+        let truncated = [0x10, 0x00, 0x00, 0x00, 0x02, 0x04, 0x00, 0x01];
+        assert!(matches!(
+            ModbusUnit::check_multi_write_byte_count(&truncated, 4),
+            Err(ModbusUnitError::EncodedByteCountMismatch { expected: 4, actual: 2 })
+        ));
+    }
+
+    fn coil_unit(length: i32, bit_order: BitOrder) -> ModbusUnit {
+        let mut builder = ModbusUnit::builder();
+        builder
+            .address(0)
+            .length(length)
+            .register_type(RegisterType::CoilRegister)
+            .coil_bit_order(bit_order);
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn coil_bit_order_symmetric_pattern_packs_the_same() {
+        let data = [1, 0, 0, 0, 0, 0, 0, 1];
+        assert_eq!(coil_unit(8, BitOrder::LsbFirst).pack_coils(&data), vec![0x81]);
+        assert_eq!(coil_unit(8, BitOrder::MsbFirst).pack_coils(&data), vec![0x81]);
+    }
+
+    #[test]
+    fn coil_bit_order_asymmetric_pattern_packs_differently() {
+        let data = [1, 1, 0, 0, 0, 0, 0, 0];
+        let lsb = coil_unit(8, BitOrder::LsbFirst);
+        let msb = coil_unit(8, BitOrder::MsbFirst);
+        assert_eq!(lsb.pack_coils(&data), vec![0x03]);
+        assert_eq!(msb.pack_coils(&data), vec![0xC0]);
+
+        assert_eq!(lsb.parse_coils(&[0x01, 0x01, 0x03], 8).unwrap(), data);
+        assert_eq!(msb.parse_coils(&[0x01, 0x01, 0xC0], 8).unwrap(), data);
+    }
+
+    #[test]
+    fn coil_roundtrip_single_coil_honors_bit_order() {
+        for bit_order in [BitOrder::LsbFirst, BitOrder::MsbFirst] {
+            let unit = coil_unit(1, bit_order);
+            assert!(unit.verify_coil_roundtrip(&[1]).is_ok());
+            assert!(unit.verify_coil_roundtrip(&[0]).is_ok());
+        }
+        assert!(coil_unit(9, BitOrder::MsbFirst).verify_coil_roundtrip(&[1, 0, 1, 1, 0, 0, 0, 1, 1]).is_ok());
+    }
+
+    #[test]
+    fn odd_length_rejects_32_bit_values() {
+        let unit = unit(RegisterType::HoldingRegister, 0, 3);
+        assert!(matches!(
+            unit.get_f32_write_request(&[1.0]),
+            Err(ModbusUnitError::LengthNotMultiple { length: 3, registers: 2 })
+        ));
+        assert!(matches!(
+            unit.parse_as_u32(&[0x03, 0x06, 0, 0, 0, 0, 0, 0]),
+            Err(ModbusUnitError::LengthNotMultiple { length: 3, registers: 2 })
+        ));
+    }
+
+    #[test]
+    fn force_multi_write_applies_to_a_single_value() {
+        let single = unit(RegisterType::HoldingRegister, 0, 1);
+        single.set(0, 7).unwrap();
+        assert_eq!(single.get_write_request().unwrap()[0], function_code::WRITE_SINGLE_REGISTER);
+
+        let mut builder = ModbusUnit::builder();
+        builder
+            .address(0)
+            .length(1)
+            .register_type(RegisterType::HoldingRegister)
+            .force_multi_write(true);
+        let multi = builder.build().unwrap();
+        multi.set(0, 7).unwrap();
+        assert_eq!(
+            multi.get_write_request().unwrap(),
+            vec![function_code::WRITE_MULTIPLE_REGISTERS, 0x00, 0x00, 0x00, 0x01, 0x02, 0x00, 0x07]
+        );
+    }
+
+    #[test]
+    fn data_address_round_trips_for_each_register_type() {
+        let cases = [
+            (RegisterType::CoilRegister, 1),
+            (RegisterType::DiscreteRegister, 10_001),
+            (RegisterType::InputRegister, 30_001),
+            (RegisterType::HoldingRegister, 40_001),
+        ];
+        for (register_type, conventional) in cases {
+            assert_eq!(unit(register_type, 0, 1).to_data_address(), conventional);
+
+            let address = unit(register_type, 99, 1).to_data_address();
+            assert_eq!(address / 10_000, conventional / 10_000);
+            assert_eq!(address % 10_000 - 1, 99);
+        }
+        assert_eq!(unit(RegisterType::HoldingRegister, 9999, 1).to_data_address(), 410_000);
+    }
+
+    #[test]
+    fn write_ack_parses_only_through_the_write_path() {
+        let unit = unit(RegisterType::HoldingRegister, 0, 2);
+        let ack = [function_code::WRITE_MULTIPLE_REGISTERS, 0x00, 0x00, 0x00, 0x02];
+        assert!(unit.parse_write_response(&ack).is_ok());
+        assert!(matches!(
+            unit.parse_response(&ack),
+            Err(ModbusUnitError::UnexpectedFunctionCode(0x03, 0x10))
+        ));
+
+        let read = [function_code::READ_HOLDING_REGISTERS, 0x04, 0x00, 0x01, 0x00, 0x02];
+        assert!(matches!(
+            unit.parse_write_response(&read),
+            Err(ModbusUnitError::UnexpectedFunctionCode(0x10, 0x03))
+        ));
+    }
+
+    #[test]
+    fn wide_byte_count_reads_a_300_byte_response() {
+        let mut builder = ModbusUnit::builder();
+        builder
+            .address(0)
+            .length(150)
+            .register_type(RegisterType::HoldingRegister)
+            .wide_byte_count(true);
+        let wide = builder.build().unwrap();
+
+        let mut pdu = vec![function_code::READ_HOLDING_REGISTERS, 0x01, 0x2C];
+        for i in 0..150u16 {
+            pdu.extend(i.to_be_bytes());
+        }
+        wide.parse_response(&pdu).unwrap();
+        assert_eq!(wide.values(), (0..150).collect::<Vec<u16>>());
+
+        let narrow = unit(RegisterType::HoldingRegister, 0, 150);
+        assert!(matches!(
+            narrow.parse_response(&pdu),
+            Err(ModbusUnitError::ByteCountMismatch { received: 0x01, .. })
+        ));
+    }
+
+    #[test]
+    fn single_coil_write_uses_the_configured_true_value() {
+        let default = unit(RegisterType::CoilRegister, 3, 1);
+        default.set(0, 1).unwrap();
+        assert_eq!(
+            default.get_write_request().unwrap(),
+            vec![function_code::WRITE_SINGLE_COIL, 0x00, 0x03, 0xFF, 0x00]
+        );
+
+        let mut builder = ModbusUnit::builder();
+        builder
+            .address(3)
+            .length(1)
+            .register_type(RegisterType::CoilRegister)
+            .coil_true_value(0x0100);
+        let custom = builder.build().unwrap();
+        custom.set(0, 1).unwrap();
+        assert_eq!(
+            custom.get_write_request().unwrap(),
+            vec![function_code::WRITE_SINGLE_COIL, 0x00, 0x03, 0x01, 0x00]
+        );
+    }
+
+    #[test]
+    fn count_field_is_read_in_the_configured_unit() {
+        let bytes = unit(RegisterType::HoldingRegister, 0, 2);
+        bytes.parse_response(&[0x03, 0x04, 0x00, 0x01, 0x00, 0x02]).unwrap();
+        assert_eq!(bytes.values(), vec![1, 2]);
+        assert!(bytes.parse_response(&[0x03, 0x02, 0x00, 0x01, 0x00, 0x02]).is_err());
+
+        let mut builder = ModbusUnit::builder();
+        builder
+            .address(0)
+            .length(2)
+            .register_type(RegisterType::HoldingRegister)
+            .count_field_unit(CountUnit::Registers);
+        let registers = builder.build().unwrap();
+        registers.parse_response(&[0x03, 0x02, 0x00, 0x01, 0x00, 0x02]).unwrap();
+        assert_eq!(registers.values(), vec![1, 2]);
+        assert!(matches!(
+            registers.parse_response(&[0x03, 0x04, 0x00, 0x01, 0x00, 0x02]),
+            Err(ModbusUnitError::ByteCountMismatch { expected: 2, received: 4, length: 2 })
+        ));
+    }
+
+    #[test]
+    fn odd_length_string_is_padded_with_the_pad_byte() {
+        let mut builder = ModbusUnit::builder();
+        builder
+            .address(0)
+            .length(3)
+            .register_type(RegisterType::HoldingRegister)
+            .with_pad_byte(0x20);
+        let unit = builder.build().unwrap();
+        unit.set_string("ABC").unwrap();
+        assert_eq!(
+            unit.get_write_request().unwrap()[6..],
+            [b'A', b'B', b'C', 0x20, 0x20, 0x20]
+        );
+    }
+
+    #[test]
+    fn strict_mode_requires_an_explicit_length() {
+        let mut builder = ModbusUnit::builder();
+        builder.address(0).register_type(RegisterType::HoldingRegister);
+        assert_eq!(builder.build().unwrap().length(), 1);
+
+        let mut builder = ModbusUnit::builder();
+        builder
+            .address(0)
+            .register_type(RegisterType::HoldingRegister)
+            .require_explicit_length(true);
+        assert!(matches!(builder.build(), Err(ModbusUnitError::LengthIsEmpty)));
+    }
+
+    #[test]
+    fn mei_passthrough_round_trips_the_payload() {
+        let unit = unit(RegisterType::HoldingRegister, 0, 1);
+        let request = unit.create_mei_request(0x0D, &[0x01, 0x02]);
+        assert_eq!(request, vec![function_code::ENCAPSULATED_INTERFACE_TRANSPORT, 0x0D, 0x01, 0x02]);
+        assert_eq!(unit.parse_mei(&request).unwrap(), (0x0D, vec![0x01, 0x02]));
+    }
+
+    #[test]
+    fn mock_coil_response_uses_the_unit_bit_order() {
+        let request = [function_code::READ_COILS, 0x00, 0x00, 0x00, 0x08];
+        let values = [1, 1, 0, 0, 0, 0, 0, 0];
+        let lsb = coil_unit(8, BitOrder::LsbFirst);
+        let msb = coil_unit(8, BitOrder::MsbFirst);
+        assert_eq!(lsb.mock_response_for(&request, &values).unwrap(), vec![0x01, 0x01, 0x03]);
+        assert_eq!(msb.mock_response_for(&request, &values).unwrap(), vec![0x01, 0x01, 0xC0]);
+
+        let response = msb.mock_response_for(&request, &values).unwrap();
+        msb.parse_response(&response).unwrap();
+        assert_eq!(msb.values(), values);
+    }
+
+    #[test]
+    fn coil_roundtrip_holds_for_odd_counts() {
+        for length in [7, 9, 17] {
+            let data: Vec<i32> = (0..length).map(|i| (i % 3 == 0) as i32).collect();
+            for bit_order in [BitOrder::LsbFirst, BitOrder::MsbFirst] {
+                assert!(coil_unit(length, bit_order).verify_coil_roundtrip(&data).is_ok());
+            }
+        }
+        assert!(matches!(
+            coil_unit(7, BitOrder::LsbFirst).verify_coil_roundtrip(&[0, 1, 2, 0, 0, 0, 0]),
+            Err(ModbusUnitError::InvalidCoilValue(2, 2))
+        ));
+    }
+
+    #[test]
+    fn single_coil_echo_returns_the_coil_state() {
+        let unit = unit(RegisterType::CoilRegister, 4, 1);
+        let on = [function_code::WRITE_SINGLE_COIL, 0x00, 0x04, 0xFF, 0x00];
+        let off = [function_code::WRITE_SINGLE_COIL, 0x00, 0x04, 0x00, 0x00];
+        assert!(unit.parse_single_coil_write_response(&on).unwrap());
+        assert!(!unit.parse_single_coil_write_response(&off).unwrap());
+
+        assert!(matches!(
+            unit.parse_single_coil_write_response(&[0x05, 0x00, 0x04, 0x12, 0x34]),
+            Err(ModbusUnitError::InvalidCoilEchoValue(0x1234))
+        ));
+        assert!(matches!(
+            unit.parse_single_coil_write_response(&[0x05, 0x00, 0x05, 0xFF, 0x00]),
+            Err(ModbusUnitError::AddressEchoMismatch { expected: 4, received: 5 })
+        ));
+    }
+
+    #[test]
+    fn sparse_write_ack_rejects_truncated_requests() {
+        let mut builder = ModbusUnit::builder();
+        builder
+            .address(0)
+            .length(2)
+            .register_type(RegisterType::HoldingRegister)
+            .sparse_writes(true);
+        let unit = builder.build().unwrap();
+        let ack = [function_code::WRITE_SINGLE_REGISTER, 0x00, 0x01, 0x00, 0x07];
+        assert!(unit.check_write_ack(&ack, &ack).is_ok());
+        assert!(matches!(
+            unit.check_write_ack(&ack, &[function_code::WRITE_SINGLE_REGISTER, 0x00]),
+            Err(ModbusUnitError::InvalidRequestLength)
+        ));
+        assert!(matches!(unit.check_write_ack(&ack, &[]), Err(ModbusUnitError::InvalidRequestLength)));
+    }
+
+    #[test]
+    fn last_values_follow_every_parse() {
+        let unit = unit(RegisterType::HoldingRegister, 0, 1);
+        assert_eq!(unit.last_values(), None);
+
+        unit.parse_response_cached(&[0x03, 0x02, 0x00, 0x0A]).unwrap();
+        let cached_at = unit.last_updated().unwrap();
+        unit.parse_response(&[0x03, 0x02, 0x00, 0x0B]).unwrap();
+        assert_eq!(unit.last_values(), Some(vec![0x0B]));
+        assert!(unit.last_updated().unwrap() >= cached_at);
+        assert_eq!(unit.snapshot().last_values, vec![0x0B]);
+    }
+
+    #[test]
+    fn mock_read_response_matches_the_unit_parser() {
+        let mut builder = ModbusUnit::builder();
+        builder
+            .address(0)
+            .length(2)
+            .register_type(RegisterType::HoldingRegister)
+            .count_field_unit(CountUnit::Registers)
+            .with_read_cmd(0x41);
+        let unit = builder.build().unwrap();
+        let request = unit.create_read_request().unwrap();
+        assert_eq!(request[0], 0x41);
+
+        let response = unit.mock_response_for(&request, &[7, 8]).unwrap();
+        assert_eq!(response, vec![function_code::READ_HOLDING_REGISTERS, 0x02, 0x00, 0x07, 0x00, 0x08]);
+        unit.parse_response(&response).unwrap();
+        assert_eq!(unit.values(), vec![7, 8]);
+
+        let mut builder = ModbusUnit::builder();
+        builder
+            .address(0)
+            .length(1)
+            .register_type(RegisterType::HoldingRegister)
+            .response_has_byte_count(false);
+        let bare = builder.build().unwrap();
+        let request = bare.create_read_request().unwrap();
+        let response = bare.mock_response_for(&request, &[9]).unwrap();
+        assert_eq!(response, vec![function_code::READ_HOLDING_REGISTERS, 0x00, 0x09]);
+        bare.parse_response(&response).unwrap();
+        assert_eq!(bare.values(), vec![9]);
+    }
+
+    #[test]
+    fn mock_write_responses_echo_the_request() {
+        let single = unit(RegisterType::HoldingRegister, 3, 1);
+        single.set(0, 0x1234).unwrap();
+        let request = single.get_write_request().unwrap();
+        let response = single.mock_response_for(&request, &[]).unwrap();
+        assert_eq!(response, request);
+        assert!(single.parse_write_response(&response).is_ok());
+
+        let multi = unit(RegisterType::HoldingRegister, 3, 2);
+        multi.set(0, 1).unwrap();
+        multi.set(1, 2).unwrap();
+        let request = multi.get_write_request().unwrap();
+        let response = multi.mock_response_for(&request, &[]).unwrap();
+        assert_eq!(response, request[..5].to_vec());
+        assert_eq!(multi.parse_multi_write_response(&response).unwrap(), 2);
+
+        assert!(matches!(
+            multi.mock_response_for(&[0x2B, 0x0E, 0x01, 0x00, 0x00], &[]),
+            Err(ModbusUnitError::UnsupportedFunctionCode(0x2B))
+        ));
+    }
+
+    #[test]
+    fn exception_status_request_is_bare_function_code() {
+        let unit = unit(RegisterType::HoldingRegister, 100, 4);
+        assert_eq!(unit.get_read_exception_status_request(), vec![0x07]);
+    }
+
+    #[test]
+    fn parse_exception_status_returns_status_byte() {
+        let unit = unit(RegisterType::HoldingRegister, 0, 1);
+        assert_eq!(unit.parse_exception_status(&[0x07, 0x6D]).unwrap(), 0x6D);
+        assert!(matches!(
+            unit.parse_exception_status(&[0x07]),
+            Err(ModbusUnitError::InvalidResponseLength)
+        ));
+        assert!(matches!(
+            unit.parse_exception_status(&[0x87, 0x01]),
+            Err(ModbusUnitError::ModbusException(0x87, 0x01))
+        ));
+    }
+
+    #[test]
+    fn diagnostics_request_carries_sub_function_and_data() {
+        let unit = unit(RegisterType::HoldingRegister, 0, 1);
+        assert_eq!(
+            unit.get_diagnostics_request(function_code::DIAG_RETURN_QUERY_DATA, 0xA537),
+            vec![0x08, 0x00, 0x00, 0xA5, 0x37]
+        );
+    }
+
+    #[test]
+    fn parse_diagnostics_response_returns_echo() {
+        let unit = unit(RegisterType::HoldingRegister, 0, 1);
+        let echo = [0x08, 0x00, 0x00, 0xA5, 0x37];
+        assert_eq!(unit.parse_diagnostics_response(&echo).unwrap(), (0x0000, 0xA537));
+        assert!(unit.check_loopback(&echo, 0xA537).unwrap());
+        assert!(!unit.check_loopback(&echo, 0xA538).unwrap());
+        assert!(matches!(
+            unit.parse_diagnostics_response(&echo[..4]),
+            Err(ModbusUnitError::InvalidResponseLength)
+        ));
+        assert!(matches!(
+            unit.parse_diagnostics_response(&[0x88, 0x03]),
+            Err(ModbusUnitError::ModbusException(0x88, 0x03))
+        ));
+    }
+
+    #[test]
+    fn report_server_id_request_is_bare_function_code() {
+        let unit = unit(RegisterType::HoldingRegister, 0, 1);
+        assert_eq!(unit.get_report_server_id_request(), vec![0x11]);
+    }
+
+    #[test]
+    fn parse_report_server_id_defaults_to_trailing_run_indicator() {
+        let unit = unit(RegisterType::HoldingRegister, 0, 1);
+        let report = unit.parse_report_server_id(&[0x11, 0x04, b'A', b'B', b'C', 0xFF]).unwrap();
+        assert_eq!(report.server_id, b"ABC".to_vec());
+        assert_eq!(report.run_indicator, Some(true));
+        assert!(report.additional_data.is_empty());
+
+        assert!(matches!(
+            unit.parse_report_server_id(&[0x11, 0x05, b'A', b'B', b'C', 0xFF]),
+            Err(ModbusUnitError::InvalidResponseLength)
+        ));
+        assert!(matches!(
+            unit.parse_report_server_id(&[0x91, 0x01]),
+            Err(ModbusUnitError::ModbusException(0x91, 0x01))
+        ));
+    }
+
+    #[test]
+    fn parse_report_server_id_after_id_splits_additional_data() {
+        let mut builder = ModbusUnit::builder();
+        builder
+            .address(0)
+            .length(1)
+            .register_type(RegisterType::HoldingRegister)
+            .run_indicator_position(RunIndicatorPos::AfterId(2));
+        let unit = builder.build().unwrap();
+
+        let report = unit.parse_report_server_id(&[0x11, 0x05, 0x12, 0x34, 0x00, b'v', b'2']).unwrap();
+        assert_eq!(report.server_id, vec![0x12, 0x34]);
+        assert_eq!(report.run_indicator, Some(false));
+        assert_eq!(report.additional_data, b"v2".to_vec());
+
+        assert!(matches!(
+            unit.parse_report_server_id(&[0x11, 0x03, 0x12, 0x34, 0x01]),
+            Err(ModbusUnitError::InvalidRunIndicator(0x01))
+        ));
+    }
+
+    #[test]
+    fn device_identification_request_layout() {
+        let unit = unit(RegisterType::HoldingRegister, 0, 1);
+        assert_eq!(unit.get_device_identification_request(0x01, 0x00), vec![0x2B, 0x0E, 0x01, 0x00]);
+    }
+
+    #[test]
+    fn parse_device_identification_walks_objects_and_paging() {
+        let unit = unit(RegisterType::HoldingRegister, 0, 1);
+        let first_page = [
+            0x2B, 0x0E, 0x01, 0x01, 0xFF, 0x02, 0x02,
+            0x00, 0x04, b'A', b'c', b'm', b'e',
+            0x01, 0x02, b'X', b'1',
+        ];
+        let identification = unit.parse_device_identification(&first_page).unwrap();
+        assert_eq!(identification.conformity_level, 0x01);
+        assert!(identification.more_follows);
+        assert_eq!(identification.next_object_id, 0x02);
+        assert_eq!(identification.objects, vec![(0x00, b"Acme".to_vec()), (0x01, b"X1".to_vec())]);
+
+        let last_page = [0x2B, 0x0E, 0x01, 0x01, 0x00, 0x00, 0x01, 0x02, 0x03, b'1', b'.', b'0'];
+        let identification = unit.parse_device_identification(&last_page).unwrap();
+        assert!(!identification.more_follows);
+        assert_eq!(identification.objects, vec![(0x02, b"1.0".to_vec())]);
+
+        assert!(matches!(
+            unit.parse_device_identification(&first_page[..first_page.len() - 1]),
+            Err(ModbusUnitError::InvalidResponseLength)
+        ));
+        assert!(matches!(
+            unit.parse_device_identification(&[0xAB, 0x02]),
+            Err(ModbusUnitError::ModbusException(0xAB, 0x02))
+        ));
+    }
+
+    #[test]
+    fn read_fifo_request_carries_pointer() {
+        let unit = unit(RegisterType::HoldingRegister, 0, 1);
+        assert_eq!(unit.get_read_fifo_request(0x04DE), vec![0x18, 0x04, 0xDE]);
+    }
+
+    #[test]
+    fn parse_fifo_response_counts_the_fifo_count_field() {
+        let unit = unit(RegisterType::HoldingRegister, 0, 1);
+        // byte_count = 2 (fifo count) + 2 * 2 (values)
+        let pdu = [0x18, 0x00, 0x06, 0x00, 0x02, 0x01, 0xB8, 0x12, 0x84];
+        assert_eq!(unit.parse_fifo_response(&pdu).unwrap(), vec![0x01B8, 0x1284]);
+
+        let data_only_count = [0x18, 0x00, 0x04, 0x00, 0x02, 0x01, 0xB8, 0x12, 0x84];
+        assert!(matches!(
+            unit.parse_fifo_response(&data_only_count),
+            Err(ModbusUnitError::InvalidResponseLength)
+        ));
+        assert!(matches!(
+            unit.parse_fifo_response(&[0x18, 0x00, 0x42, 0x00, 0x20]),
+            Err(ModbusUnitError::FifoCountTooLarge(32))
+        ));
+        assert!(matches!(
+            unit.parse_fifo_response(&[0x98, 0x03]),
+            Err(ModbusUnitError::ModbusException(0x98, 0x03))
+        ));
+    }
+
+    fn ordered_unit(length: i32, word_order: WordOrder, byte_order: Endianness) -> ModbusUnit {
+        let mut builder = ModbusUnit::builder();
+        builder
+            .address(0)
+            .length(length)
+            .register_type(RegisterType::HoldingRegister)
+            .word_order(word_order)
+            .byte_order(byte_order);
+        builder.build().unwrap()
+    }
+
+    fn read_pdu(data: &[u8]) -> Vec<u8> {
+        let mut pdu = vec![function_code::READ_HOLDING_REGISTERS, data.len() as u8];
+        pdu.extend_from_slice(data);
+        pdu
+    }
+
+    #[test]
+    fn u32_and_i32_follow_word_and_byte_order() {
+        let table = [
+            (WordOrder::HighWordFirst, Endianness::Big, [0x12, 0x34, 0x56, 0x78, 0xFE, 0xDC, 0xBA, 0x98]),
+            (WordOrder::LowWordFirst, Endianness::Big, [0x56, 0x78, 0x12, 0x34, 0xBA, 0x98, 0xFE, 0xDC]),
+            (WordOrder::HighWordFirst, Endianness::Little, [0x34, 0x12, 0x78, 0x56, 0xDC, 0xFE, 0x98, 0xBA]),
+            (WordOrder::LowWordFirst, Endianness::Little, [0x78, 0x56, 0x34, 0x12, 0x98, 0xBA, 0xDC, 0xFE]),
+        ];
+        for (word_order, byte_order, wire) in table {
+            let unit = ordered_unit(4, word_order, byte_order);
+            let pdu = read_pdu(&wire);
+            assert_eq!(unit.parse_as_u32(&pdu).unwrap(), vec![0x1234_5678, 0xFEDC_BA98], "{word_order:?} {byte_order:?}");
+            assert_eq!(unit.parse_as_i32(&pdu).unwrap(), vec![0x1234_5678, -0x0123_4568], "{word_order:?} {byte_order:?}");
+            assert_eq!(unit.get_u32_write_request(&[0x1234_5678, 0xFEDC_BA98]).unwrap()[6..], wire);
+            assert_eq!(unit.get_i32_write_request(&[0x1234_5678, -0x0123_4568]).unwrap()[6..], wire);
+        }
+    }
+
+    #[test]
+    fn u64_i64_and_f64_follow_word_and_byte_order() {
+        let table = [
+            (WordOrder::HighWordFirst, Endianness::Big, [0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08]),
+            (WordOrder::LowWordFirst, Endianness::Big, [0x07, 0x08, 0x05, 0x06, 0x03, 0x04, 0x01, 0x02]),
+            (WordOrder::HighWordFirst, Endianness::Little, [0x02, 0x01, 0x04, 0x03, 0x06, 0x05, 0x08, 0x07]),
+            (WordOrder::LowWordFirst, Endianness::Little, [0x08, 0x07, 0x06, 0x05, 0x04, 0x03, 0x02, 0x01]),
+        ];
+        for (word_order, byte_order, wire) in table {
+            let unit = ordered_unit(4, word_order, byte_order);
+            let pdu = read_pdu(&wire);
+            assert_eq!(unit.parse_as_u64(&pdu).unwrap(), vec![0x0102_0304_0506_0708], "{word_order:?} {byte_order:?}");
+            assert_eq!(unit.parse_as_i64(&pdu).unwrap(), vec![0x0102_0304_0506_0708], "{word_order:?} {byte_order:?}");
+            assert_eq!(
+                unit.parse_as_f64(&pdu).unwrap(),
+                vec![f64::from_bits(0x0102_0304_0506_0708)],
+                "{word_order:?} {byte_order:?}"
+            );
+            assert_eq!(unit.get_u64_write_request(&[0x0102_0304_0506_0708]).unwrap()[6..], wire);
+        }
+
+        // -1.5 and -2 exercise the sign bit through the same shuffling
+        let unit = ordered_unit(8, WordOrder::LowWordFirst, Endianness::Little);
+        let pdu = read_pdu(&[0, 0, 0, 0, 0, 0, 0xF8, 0xBF, 0xFE, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF]);
+        assert_eq!(unit.parse_as_f64(&pdu).unwrap()[0], -1.5);
+        assert_eq!(unit.parse_as_i64(&pdu).unwrap()[1], -2);
+        assert_eq!(unit.get_i64_write_request(&[(-1.5f64).to_bits() as i64, -2]).unwrap()[6..], pdu[2..]);
+        assert_eq!(unit.get_f64_write_request(&[-1.5, f64::from_bits(u64::MAX - 1)]).unwrap()[6..], pdu[2..]);
+    }
+
+    #[test]
+    fn parse_as_string_trims_nul_and_pad_bytes() {
+        let table: [(u8, &[u8], &str); 4] = [
+            (0x00, b"Tag1\0\0\0\0", "Tag1"),
+            (0x00, b"Tag1  \0\0", "Tag1  "),
+            (0x20, b"Tag1    ", "Tag1"),
+            (0x20, b"Tag 1 \0\0", "Tag 1"),
+        ];
+        for (pad_byte, wire, expected) in table {
+            let mut builder = ModbusUnit::builder();
+            builder
+                .address(0)
+                .length(4)
+                .register_type(RegisterType::HoldingRegister)
+                .with_pad_byte(pad_byte);
+            let unit = builder.build().unwrap();
+            assert_eq!(unit.parse_as_string(&read_pdu(wire)).unwrap(), expected, "{wire:?}");
+        }
+
+        let mut builder = ModbusUnit::builder();
+        builder
+            .address(0)
+            .length(4)
+            .register_type(RegisterType::HoldingRegister)
+            .with_pad_byte(0x20);
+        let unit = builder.build().unwrap();
+        assert_eq!(unit.get_string_write_request("Tag1").unwrap()[6..], *b"Tag1    ");
+    }
+
+    #[test]
+    fn wrong_byte_count_reports_byte_count_mismatch() {
+        let registers = unit(RegisterType::HoldingRegister, 0, 2);
+        assert!(matches!(
+            registers.parse_response(&[0x03, 0x02, 0x00, 0x01]),
+            Err(ModbusUnitError::ByteCountMismatch { expected: 4, received: 2, length: 2 })
+        ));
+
+        let coils = coil_unit(9, BitOrder::LsbFirst);
+        assert!(matches!(
+            coils.parse_response(&[0x01, 0x01, 0xFF]),
+            Err(ModbusUnitError::ByteCountMismatch { expected: 2, received: 1, length: 9 })
+        ));
+    }
+
+    #[test]
+    fn short_data_reports_truncated_data() {
+        let registers = unit(RegisterType::HoldingRegister, 0, 2);
+        assert!(matches!(
+            registers.parse_response(&[0x03, 0x04, 0x00, 0x01, 0x00]),
+            Err(ModbusUnitError::TruncatedData { need: 4, have: 3 })
+        ));
+
+        let coils = coil_unit(9, BitOrder::LsbFirst);
+        assert!(matches!(
+            coils.parse_response(&[0x01, 0x02, 0xFF]),
+            Err(ModbusUnitError::TruncatedData { need: 2, have: 1 })
+        ));
+    }
+
+    #[test]
+    fn coil_edges_report_transitions_between_polls() {
+        let coils = coil_unit(4, BitOrder::LsbFirst);
+        assert!(coils.parse_coils_edges(&[0x01, 0x01, 0b0101]).unwrap().is_empty());
+
+        assert_eq!(
+            coils.parse_coils_edges(&[0x01, 0x01, 0b0011]).unwrap(),
+            vec![Edge { index: 1, rising: true }, Edge { index: 2, rising: false }]
+        );
+        assert!(coils.parse_coils_edges(&[0x01, 0x01, 0b0011]).unwrap().is_empty());
+
+        assert!(coils.parse_coils_edges(&[0x01, 0x02, 0x00]).is_err());
+        assert_eq!(coils.values(), vec![1, 1, 0, 0]);
+        assert!(matches!(
+            unit(RegisterType::HoldingRegister, 0, 1).parse_coils_edges(&[0x03, 0x02, 0x00, 0x01]),
+            Err(ModbusUnitError::NotBitData(RegisterType::HoldingRegister))
+        ));
+    }
+
+    #[test]
+    fn build_lists_every_invalid_field() {
+        let builder = ModbusUnit::builder();
+        assert!(matches!(
+            builder.build(),
+            Err(ModbusUnitError::BuilderIncomplete(fields)) if fields == ["address", "register_type"]
+        ));
+
+        let mut builder = ModbusUnit::builder();
+        builder
+            .address(0)
+            .length(2)
+            .register_type(RegisterType::HoldingRegister)
+            .custom_commands(0x41, 256, -1);
+        assert!(matches!(
+            builder.build(),
+            Err(ModbusUnitError::BuilderIncomplete(fields)) if fields == ["write_cmd", "multi_write_cmd"]
+        ));
+
+        // A single problem keeps its specific error
+        let mut builder = ModbusUnit::builder();
+        builder.address(0).length(2).register_type(RegisterType::HoldingRegister).with_read_cmd(300);
+        assert!(matches!(builder.build(), Err(ModbusUnitError::InvalidReadCommand(300))));
+
+        let mut builder = ModbusUnit::builder();
+        builder.length(2).register_type(RegisterType::HoldingRegister);
+        assert!(matches!(builder.build(), Err(ModbusUnitError::AddressIsEmpty)));
+    }
 }
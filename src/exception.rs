@@ -0,0 +1,101 @@
+use super::*;
+
+/// Exception codes defined by the Modbus application protocol
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ModbusExceptionCode {
+    IllegalFunction,
+    IllegalDataAddress,
+    IllegalDataValue,
+    ServerDeviceFailure,
+    Acknowledge,
+    ServerDeviceBusy,
+    MemoryParityError,
+    GatewayPathUnavailable,
+    GatewayTargetDeviceFailedToRespond,
+    /// Code not defined by the spec
+    Unknown(u8),
+}
+
+impl ModbusExceptionCode {
+    pub fn from_code(code: u8) -> Self {
+        match code {
+            0x01 => ModbusExceptionCode::IllegalFunction,
+            0x02 => ModbusExceptionCode::IllegalDataAddress,
+            0x03 => ModbusExceptionCode::IllegalDataValue,
+            0x04 => ModbusExceptionCode::ServerDeviceFailure,
+            0x05 => ModbusExceptionCode::Acknowledge,
+            0x06 => ModbusExceptionCode::ServerDeviceBusy,
+            0x08 => ModbusExceptionCode::MemoryParityError,
+            0x0A => ModbusExceptionCode::GatewayPathUnavailable,
+            0x0B => ModbusExceptionCode::GatewayTargetDeviceFailedToRespond,
+            other => ModbusExceptionCode::Unknown(other),
+        }
+    }
+
+    pub fn code(&self) -> u8 {
+        match self {
+            ModbusExceptionCode::IllegalFunction => 0x01,
+            ModbusExceptionCode::IllegalDataAddress => 0x02,
+            ModbusExceptionCode::IllegalDataValue => 0x03,
+            ModbusExceptionCode::ServerDeviceFailure => 0x04,
+            ModbusExceptionCode::Acknowledge => 0x05,
+            ModbusExceptionCode::ServerDeviceBusy => 0x06,
+            ModbusExceptionCode::MemoryParityError => 0x08,
+            ModbusExceptionCode::GatewayPathUnavailable => 0x0A,
+            ModbusExceptionCode::GatewayTargetDeviceFailedToRespond => 0x0B,
+            ModbusExceptionCode::Unknown(code) => *code,
+        }
+    }
+
+    /// Explanation of the exception as given in the Modbus specification
+    pub fn describe(&self) -> &'static str {
+        match self {
+            ModbusExceptionCode::IllegalFunction =>
+                "The function code received in the query is not an allowable action for the server",
+            ModbusExceptionCode::IllegalDataAddress =>
+                "The data address received in the query is not an allowable address for the server",
+            ModbusExceptionCode::IllegalDataValue =>
+                "A value contained in the query data field is not an allowable value for the server",
+            ModbusExceptionCode::ServerDeviceFailure =>
+                "An unrecoverable error occurred while the server was attempting to perform the requested action",
+            ModbusExceptionCode::Acknowledge =>
+                "The server has accepted the request and is processing it, but a long duration of time will be required",
+            ModbusExceptionCode::ServerDeviceBusy =>
+                "The server is engaged in processing a long-duration program command",
+            ModbusExceptionCode::MemoryParityError =>
+                "The server attempted to read a record file but detected a parity error in the memory",
+            ModbusExceptionCode::GatewayPathUnavailable =>
+                "The gateway was unable to allocate an internal communication path for the request",
+            ModbusExceptionCode::GatewayTargetDeviceFailedToRespond =>
+                "No response was obtained from the target device behind the gateway",
+            ModbusExceptionCode::Unknown(_) => "Exception code not defined by the Modbus specification",
+        }
+    }
+}
+
+impl ModbusUnitError {
+    /// Named exception code of a `ModbusException`, `None` for other errors
+    pub fn exception_code(&self) -> Option<ModbusExceptionCode> {
+        match self {
+            ModbusUnitError::ModbusException(_, code) => Some(ModbusExceptionCode::from_code(*code)),
+            _ => None,
+        }
+    }
+
+    /// Operator-facing text for a `ModbusException`, including the
+    /// originating function code, e.g. "function 0x03, exception 0x02: ..."
+    pub fn describe_exception(&self) -> Option<String> {
+        match self {
+            ModbusUnitError::ModbusException(function_code, code) => {
+                let exception = ModbusExceptionCode::from_code(*code);
+                Some(format!(
+                    "function {:#04x}, exception {:#04x}: {}",
+                    function_code & !function_code::EXCEPTION_OFFSET,
+                    code,
+                    exception.describe()
+                ))
+            }
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,26 @@
+//! Standard Modbus function codes
+
+pub const READ_COILS: u8 = 0x01;
+pub const READ_DISCRETE_INPUTS: u8 = 0x02;
+pub const READ_HOLDING_REGISTERS: u8 = 0x03;
+pub const READ_INPUT_REGISTERS: u8 = 0x04;
+pub const WRITE_SINGLE_COIL: u8 = 0x05;
+pub const WRITE_SINGLE_REGISTER: u8 = 0x06;
+pub const READ_EXCEPTION_STATUS: u8 = 0x07;
+pub const DIAGNOSTICS: u8 = 0x08;
+pub const WRITE_MULTIPLE_COILS: u8 = 0x0F;
+pub const WRITE_MULTIPLE_REGISTERS: u8 = 0x10;
+pub const REPORT_SERVER_ID: u8 = 0x11;
+pub const MASK_WRITE_REGISTER: u8 = 0x16;
+pub const READ_WRITE_MULTIPLE_REGISTERS: u8 = 0x17;
+pub const READ_FIFO_QUEUE: u8 = 0x18;
+pub const ENCAPSULATED_INTERFACE_TRANSPORT: u8 = 0x2B;
+
+/// Encapsulated Interface Transport (0x2B) MEI type for device identification
+pub const MEI_READ_DEVICE_IDENTIFICATION: u8 = 0x0E;
+
+/// Diagnostics (0x08) sub-function that echoes the request data
+pub const DIAG_RETURN_QUERY_DATA: u16 = 0x0000;
+
+/// Set on the function code of an exception response
+pub const EXCEPTION_OFFSET: u8 = 0x80;
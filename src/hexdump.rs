@@ -0,0 +1,18 @@
+/// Hex dump of `frame` in the offset-prefixed layout accepted by Wireshark's
+/// "Import from Hex Dump" and text2pcap, 16 bytes per line
+pub fn frame_to_tshark_hex(frame: &[u8]) -> String {
+    frame
+        .chunks(16)
+        .enumerate()
+        .map(|(line, bytes)| {
+            let hex: Vec<String> = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+            format!("{:06x} {}\n", line * 16, hex.join(" "))
+        })
+        .collect()
+}
+
+/// `frame_to_tshark_hex` with `timestamp` before the first offset, for
+/// imports that set a timestamp format (e.g. text2pcap -t)
+pub fn frame_to_tshark_hex_at(frame: &[u8], timestamp: &str) -> String {
+    format!("{} {}", timestamp, frame_to_tshark_hex(frame))
+}
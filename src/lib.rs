@@ -1,14 +1,44 @@
 // lib.rs
 
+#[macro_use]
+mod builder;
+mod alarm;
 mod core;
+mod convert;
+mod exception;
+mod hexdump;
+mod modbus_ascii;
 mod modbus_tcp;
 mod modbus_rtu;
+pub mod function_code;
+mod scheduler;
+mod schema;
+mod session;
+mod snapshot;
+mod transport;
 
-pub use core::{RegisterType};
-pub use modbus_rtu::{ModbusRTU, ModbusRTUBuilder};
+pub use alarm::{AlarmMap, Polarity};
+pub use core::{BitOrder, ByteOrderGuess, CountUnit, DeviceIdentification, DiscreteSummary, Edge, RegisterType, RegisterValue, RunIndicatorPos, ServerIdReport, WriteMode};
+pub use convert::{
+    Endianness, WordOrder, bytes_to_registers, f32_to_registers, f64_to_registers, i8_pairs_to_registers,
+    registers_to_f32, registers_to_f64, registers_to_i8_pairs, registers_to_timestamp, registers_to_u32,
+    registers_to_u64, string_to_registers, u32_to_registers, u64_to_registers,
+};
+#[cfg(feature = "chrono")]
+pub use convert::registers_to_datetime;
+pub use exception::ModbusExceptionCode;
+pub use hexdump::{frame_to_tshark_hex, frame_to_tshark_hex_at};
+pub use modbus_ascii::{ModbusASCII, ModbusASCIIBuilder, lrc};
+pub use modbus_rtu::{Crc16, ModbusRTU, ModbusRTUBuilder, build_rtu_read};
 pub use modbus_tcp::{ModbusTCPUnit, ModbusTCPUnitBuilder};
+pub use scheduler::PollScheduler;
+pub use schema::{FieldType, Layout, Schema, Value};
+pub use session::{SessionAnalyzer, SessionStats};
+pub use snapshot::{UnitConfig, UnitSnapshot};
+pub use transport::Transport;
 
-pub use core::{ModbusUnit, ModbusUnitBuilder, ModbusUnitError};
+pub use core::{ModbusUnit, ModbusUnitBuilder, ModbusUnitError, decode_coils_from_response};
+use builder::build_unit;
 
 #[derive(Debug, thiserror::Error)]
 pub enum ModbusTransportError {
@@ -35,4 +65,37 @@ pub enum ModbusTransportError {
 
     #[error("Invalid index at set")]
     InvalidIndexAtSet,
+
+    #[error("Response does not match request: {field} expected {expected:#x}, received {received:#x}")]
+    ResponseDoesNotMatchRequest { field: &'static str, expected: u8, received: u8 },
+
+    #[error("Response received with no outstanding request")]
+    UnsolicitedResponse,
+
+    #[error("Trailing partial frame: {0} bytes left in buffer")]
+    TrailingPartialFrame(usize),
+
+    #[error("Read timed out after {received} of {expected} bytes")]
+    ReadTimeout { received: usize, expected: usize },
+
+    #[error("Too many outstanding transactions, limit {0}")]
+    TooManyOutstanding(usize),
+
+    #[error("Builder errors: {}", .0.iter().map(|e| e.to_string()).collect::<Vec<_>>().join("; "))]
+    BuildErrors(Vec<ModbusTransportError>),
+
+    #[error("Function code {0:#04x} not allowed")]
+    FunctionCodeNotAllowed(u8),
+
+    #[error("ASCII frame does not start with ':'")]
+    MissingStartChar,
+
+    #[error("Malformed hex in ASCII frame")]
+    InvalidHex,
+
+    #[error("LRC mismatch: expected {expected:#04x}, received {received:#04x}")]
+    LrcMismatch { expected: u8, received: u8 },
+
+    #[error("Transaction ID mismatch: expected {expected}, received {received}")]
+    TransactionIdMismatch { expected: u16, received: u16 },
 }
\ No newline at end of file
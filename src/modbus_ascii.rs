@@ -0,0 +1,267 @@
+use super::*;
+
+pub struct ModbusASCIIBuilder {
+    unit_builder: ModbusUnitBuilder,
+    device_id: Option<u8>,
+}
+
+impl ModbusASCIIBuilder {
+    forward_unit_builder!();
+
+    pub fn device_id(mut self, device_id: u8) -> Self {
+        self.device_id = Some(device_id);
+        self
+    }
+
+    pub fn build(self) -> Result<ModbusASCII, ModbusTransportError> {
+        let (unit, device_id) = build_unit(self.unit_builder, self.device_id)?;
+
+        Ok(ModbusASCII {
+            unit,
+            device_id,
+        })
+    }
+}
+
+/// Modbus ASCII client with encapsulated protocol logic. Frames are
+/// `:` + hex(device id, PDU, LRC) + CRLF.
+pub struct ModbusASCII {
+    unit: ModbusUnit,
+    device_id: u8,
+}
+
+impl ModbusASCII {
+    /// Create new builder for Modbus ASCII
+    pub fn builder() -> ModbusASCIIBuilder {
+        ModbusASCIIBuilder {
+            unit_builder: ModbusUnit::builder(),
+            device_id: None,
+        }
+    }
+
+    /// Generate complete ASCII frame for read request
+    pub fn create_read_request(&self) -> Result<Vec<u8>, ModbusTransportError> {
+        let pdu = self.unit.create_read_request()
+            .map_err(ModbusTransportError::Protocol)?;
+        Ok(self.wrap_ascii(pdu))
+    }
+
+    /// Generate one ASCII frame per chunk of a read larger than the spec limit
+    pub fn create_read_requests(&self) -> Result<Vec<Vec<u8>>, ModbusTransportError> {
+        let pdus = self.unit.create_read_requests()
+            .map_err(ModbusTransportError::Protocol)?;
+        Ok(pdus.into_iter().map(|pdu| self.wrap_ascii(pdu)).collect())
+    }
+
+    /// Generate complete ASCII frame for write request
+    pub fn create_write_request(&self) -> Result<Vec<u8>, ModbusTransportError> {
+        let pdu = self.unit.get_write_request()
+            .map_err(ModbusTransportError::Protocol)?;
+        Ok(self.wrap_ascii(pdu))
+    }
+
+    /// Generate one ASCII frame per write PDU; several frames in sparse-write mode
+    pub fn create_write_requests(&self) -> Result<Vec<Vec<u8>>, ModbusTransportError> {
+        let pdus = self.unit.get_write_requests()
+            .map_err(ModbusTransportError::Protocol)?;
+        Ok(pdus.into_iter().map(|pdu| self.wrap_ascii(pdu)).collect())
+    }
+
+    /// Parse ASCII response and extract values
+    pub fn parse_response(&self, frame: &[u8]) -> Result<(), ModbusTransportError> {
+        let pdu = self.unwrap_ascii(frame)?;
+        self.unit.parse_response(&pdu)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    /// Parse the responses to `create_read_requests`, in order
+    pub fn parse_responses(&self, frames: &[Vec<u8>]) -> Result<(), ModbusTransportError> {
+        let pdus = frames
+            .iter()
+            .map(|frame| self.unwrap_ascii(frame))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.unit.parse_responses(&pdus)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    /// Parse ASCII write acknowledgement
+    pub fn parse_write_response(&self, frame: &[u8]) -> Result<(), ModbusTransportError> {
+        let pdu = self.unwrap_ascii(frame)?;
+        self.unit.parse_write_response(&pdu)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    /// Parse the acknowledgements to the frames from `create_write_requests`, in order
+    pub fn parse_write_responses(&self, requests: &[Vec<u8>], frames: &[Vec<u8>]) -> Result<(), ModbusTransportError> {
+        if requests.len() != frames.len() {
+            return Err(ModbusUnitError::ResponseCountMismatch {
+                expected: requests.len(),
+                actual: frames.len(),
+            }.into());
+        }
+        for (request, frame) in requests.iter().zip(frames) {
+            let request_pdu = self.unwrap_ascii(request)?;
+            let pdu = self.unwrap_ascii(frame)?;
+            self.unit.check_write_ack(&pdu, &request_pdu)?;
+        }
+        Ok(())
+    }
+
+    /// Parse ASCII write-multiple acknowledgement, returning the echoed quantity
+    pub fn parse_multi_write_response(&self, frame: &[u8]) -> Result<u16, ModbusTransportError> {
+        let pdu = self.unwrap_ascii(frame)?;
+        self.unit.parse_multi_write_response(&pdu)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    fn wrap_ascii(&self, pdu: Vec<u8>) -> Vec<u8> {
+        let mut body = Vec::with_capacity(1 + pdu.len() + 1);
+        body.push(self.device_id);
+        body.extend(&pdu);
+        body.push(lrc(&body));
+
+        let mut frame = Vec::with_capacity(1 + body.len() * 2 + 2);
+        frame.push(b':');
+        for byte in body {
+            frame.extend(format!("{byte:02X}").bytes());
+        }
+        frame.extend(b"\r\n");
+
+        frame
+    }
+
+    fn unwrap_ascii(&self, frame: &[u8]) -> Result<Vec<u8>, ModbusTransportError> {
+        if frame.first() != Some(&b':') {
+            return Err(ModbusTransportError::MissingStartChar);
+        }
+        // ':' + device id + function code + LRC + CRLF
+        if frame.len() < 9 || !frame.ends_with(b"\r\n") {
+            return Err(ModbusTransportError::FrameTooShort);
+        }
+
+        let body = decode_hex(&frame[1..frame.len() - 2])?;
+        let unit_id = body[0];
+        if unit_id != self.device_id {
+            return Err(ModbusTransportError::UnitIdMismatch {
+                expected: self.device_id,
+                received: unit_id,
+            });
+        }
+
+        let received_lrc = body[body.len() - 1];
+        let calculated_lrc = lrc(&body[..body.len() - 1]);
+        if received_lrc != calculated_lrc {
+            return Err(ModbusTransportError::LrcMismatch {
+                expected: calculated_lrc,
+                received: received_lrc,
+            });
+        }
+
+        Ok(body[1..body.len() - 1].to_vec())
+    }
+}
+
+/// Modbus ASCII longitudinal redundancy check: two's complement of the byte sum
+pub fn lrc(data: &[u8]) -> u8 {
+    data.iter()
+        .fold(0u8, |sum, &byte| sum.wrapping_add(byte))
+        .wrapping_neg()
+}
+
+/// Decode pairs of ASCII hex digits into bytes
+fn decode_hex(hex: &[u8]) -> Result<Vec<u8>, ModbusTransportError> {
+    if !hex.len().is_multiple_of(2) {
+        return Err(ModbusTransportError::InvalidHex);
+    }
+    let digit = |c: u8| (c as char).to_digit(16).ok_or(ModbusTransportError::InvalidHex);
+    hex.chunks_exact(2)
+        .map(|pair| Ok((digit(pair[0])? << 4 | digit(pair[1])?) as u8))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn holding_unit(length: i32) -> ModbusASCIIBuilder {
+        ModbusASCII::builder()
+            .address(0)
+            .length(length)
+            .register_type(RegisterType::HoldingRegister)
+            .device_id(1)
+    }
+
+    #[test]
+    fn sparse_write_acks_match_every_frame() {
+        let unit = holding_unit(4).sparse_writes(true).build().unwrap();
+        unit.unit.set(1, 20).unwrap();
+        unit.unit.set(2, 30).unwrap();
+        let requests = unit.create_write_requests().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert!(unit.parse_write_responses(&requests, &requests).is_ok());
+
+        let swapped = vec![requests[1].clone(), requests[0].clone()];
+        assert!(matches!(
+            unit.parse_write_responses(&requests, &swapped),
+            Err(ModbusTransportError::Protocol(ModbusUnitError::AddressEchoMismatch { expected: 1, received: 2 }))
+        ));
+    }
+
+    #[test]
+    fn lrc_is_the_negated_byte_sum() {
+        assert_eq!(lrc(&[0x01, 0x03, 0x00, 0x00, 0x00, 0x01]), 0xFB);
+        assert_eq!(lrc(&[]), 0x00);
+        assert_eq!(lrc(&[0xFF, 0x01]), 0x00);
+    }
+
+    #[test]
+    fn decode_hex_accepts_only_hex_digit_pairs() {
+        assert_eq!(decode_hex(b"0aFF").unwrap(), vec![0x0A, 0xFF]);
+        for malformed in [&b"0"[..], b"+1", b"0G", b" 1", b"-1"] {
+            assert!(matches!(decode_hex(malformed), Err(ModbusTransportError::InvalidHex)));
+        }
+    }
+
+    #[test]
+    fn read_request_and_response_round_trip() {
+        let unit = holding_unit(1).build().unwrap();
+        assert_eq!(unit.create_read_request().unwrap(), b":010300000001FB\r\n");
+
+        unit.parse_response(b":010302002AD0\r\n").unwrap();
+        assert_eq!(unit.unit.values(), vec![42]);
+    }
+
+    #[test]
+    fn write_request_is_acknowledged_by_its_echo() {
+        let unit = holding_unit(2).build().unwrap();
+        unit.unit.set(0, 0x0102).unwrap();
+        unit.unit.set(1, 0x0304).unwrap();
+        let request = unit.create_write_request().unwrap();
+        assert_eq!(request, b":0110000000020401020304DF\r\n");
+        unit.parse_write_response(b":011000000002ED\r\n").unwrap();
+        assert!(matches!(
+            unit.parse_write_response(b":011000000001EE\r\n"),
+            Err(ModbusTransportError::Protocol(ModbusUnitError::QuantityEchoMismatch { expected: 2, received: 1 }))
+        ));
+    }
+
+    #[test]
+    fn malformed_frames_are_rejected() {
+        let unit = holding_unit(1).build().unwrap();
+        assert!(matches!(
+            unit.parse_response(b"010302002AD0\r\n"),
+            Err(ModbusTransportError::MissingStartChar)
+        ));
+        assert!(matches!(unit.parse_response(b":010302002AD0"), Err(ModbusTransportError::FrameTooShort)));
+        assert!(matches!(unit.parse_response(b":010302002AD\r\n"), Err(ModbusTransportError::InvalidHex)));
+        assert!(matches!(unit.parse_response(b":01030200+AD0\r\n"), Err(ModbusTransportError::InvalidHex)));
+        assert!(matches!(
+            unit.parse_response(b":010302002AD1\r\n"),
+            Err(ModbusTransportError::LrcMismatch { expected: 0xD0, received: 0xD1 })
+        ));
+        assert!(matches!(
+            unit.parse_response(b":020302002ACF\r\n"),
+            Err(ModbusTransportError::UnitIdMismatch { expected: 1, received: 2 })
+        ));
+    }
+}
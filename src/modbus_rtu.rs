@@ -1,55 +1,67 @@
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
 use super::*;
 
 pub struct ModbusRTUBuilder {
     unit_builder: ModbusUnitBuilder,
     device_id: Option<u8>,
+    skip_leading_bytes: usize,
+    min_interval: Option<Duration>,
+    match_responses: bool,
+    defer_crc: bool,
 }
 
 impl ModbusRTUBuilder {
-    pub fn address(mut self, addr: i32) -> Self {
-        self.unit_builder.address(addr);
-        self
-    }
-
-    pub fn length(mut self, length: i32) -> Self {
-        self.unit_builder.length(length);
-        self
-    }
+    forward_unit_builder!();
 
-    pub fn register_type(mut self, register_type: RegisterType) -> Self {
-        self.unit_builder.register_type(register_type);
+    pub fn device_id(mut self, device_id: u8) -> Self {
+        self.device_id = Some(device_id);
         self
     }
 
-    pub fn with_read_cmd(mut self, spec_read_cmd: i32) -> Self {
-        self.unit_builder.with_read_cmd(spec_read_cmd);
+    /// Drop `count` bytes after the address before parsing the PDU, for
+    /// gateways that insert extra bytes. CRC still covers the whole frame.
+    pub fn skip_leading_bytes(mut self, count: usize) -> Self {
+        self.skip_leading_bytes = count;
         self
     }
 
-    pub fn with_write_cmd(mut self, spec_write_cmd: i32) -> Self {
-        self.unit_builder.with_write_cmd(spec_write_cmd);
+    /// Minimum bus quiet time between the end of one transaction and the start of the next.
+    /// A transaction ends when its response frame is parsed; request builders sleep
+    /// until the interval has elapsed before returning the next frame.
+    pub fn with_min_interval(mut self, min_interval: Duration) -> Self {
+        self.min_interval = Some(min_interval);
         self
     }
 
-    pub fn with_multi_write_cmd(mut self, multi_write_cmd: i32) -> Self {
-        self.unit_builder.with_multi_write_cmd(multi_write_cmd);
+    /// Remember the outstanding requests and reject responses that cannot
+    /// belong to them (wrong function code or ByteCount, or no request pending).
+    /// Multi-frame calls expect their responses in the order the frames were built.
+    pub fn match_responses(mut self, match_responses: bool) -> Self {
+        self.match_responses = match_responses;
         self
     }
 
-    pub fn device_id(mut self, device_id: u8) -> Self {
-        self.device_id = Some(device_id);
+    /// Append a 0x0000 placeholder instead of the CRC, for fixture tooling
+    /// that stamps the real value later with `ModbusRTU::finalize_frame`
+    pub fn defer_crc(mut self, defer_crc: bool) -> Self {
+        self.defer_crc = defer_crc;
         self
     }
 
     pub fn build(self) -> Result<ModbusRTU, ModbusTransportError> {
-        let unit = self.unit_builder.build()
-            .map_err(ModbusTransportError::Protocol)?;
-
-        let device_id = self.device_id.ok_or(ModbusTransportError::DeviceIdMissing)?;
+        let (unit, device_id) = build_unit(self.unit_builder, self.device_id)?;
 
         Ok(ModbusRTU {
             unit,
             device_id,
+            skip_leading_bytes: self.skip_leading_bytes,
+            min_interval: self.min_interval,
+            last_transaction_end: Cell::new(None),
+            match_responses: self.match_responses,
+            pending: RefCell::new(VecDeque::new()),
+            defer_crc: self.defer_crc,
         })
     }
 }
@@ -58,6 +70,19 @@ impl ModbusRTUBuilder {
 pub struct ModbusRTU {
     unit: ModbusUnit,
     device_id: u8,
+    skip_leading_bytes: usize,
+    min_interval: Option<Duration>,
+    last_transaction_end: Cell<Option<Instant>>,
+    match_responses: bool,
+    pending: RefCell<VecDeque<PendingRequest>>,
+    defer_crc: bool,
+}
+
+/// Function code and expected ByteCount of a request awaiting its response
+#[derive(Copy, Clone)]
+struct PendingRequest {
+    function_code: u8,
+    byte_count: Option<u8>,
 }
 
 impl ModbusRTU {
@@ -66,36 +91,595 @@ impl ModbusRTU {
         ModbusRTUBuilder {
             unit_builder: ModbusUnit::builder(),
             device_id: None,
+            skip_leading_bytes: 0,
+            min_interval: None,
+            match_responses: false,
+            defer_crc: false,
         }
     }
 
+    /// Flat constructor for FFI callers, validated exactly like `build()`
+    pub fn from_parts(start_addr: u16, length: u16, register_type: RegisterType, device_id: u8) -> Result<ModbusRTU, ModbusTransportError> {
+        Self::builder()
+            .address(start_addr as i32)
+            .length(length as i32)
+            .register_type(register_type)
+            .device_id(device_id)
+            .build()
+    }
+
     /// Generate complete RTU frame for read request
     pub fn create_read_request(&self) -> Result<Vec<u8>, ModbusTransportError> {
         let pdu = self.unit.create_read_request()
             .map_err(ModbusTransportError::Protocol)?;
-        Ok(self.wrap_rtu(pdu))
+        self.record_pending(pdu[0], self.unit.expected_byte_count(self.unit.length()));
+        Ok(self.frame_request(pdu))
+    }
+
+    /// Read request frame for only the first `count` values of the unit
+    pub fn create_read_request_count(&self, count: u16) -> Result<Vec<u8>, ModbusTransportError> {
+        let pdu = self.unit.create_read_request_count(count)
+            .map_err(ModbusTransportError::Protocol)?;
+        self.record_pending(pdu[0], self.unit.expected_byte_count(count));
+        Ok(self.frame_request(pdu))
+    }
+
+    /// Frame for a one-value read at the unit's start address, for health checks
+    pub fn create_probe_request(&self) -> Result<Vec<u8>, ModbusTransportError> {
+        let pdu = self.unit.create_probe_request()
+            .map_err(ModbusTransportError::Protocol)?;
+        self.record_pending(pdu[0], self.unit.expected_byte_count(1));
+        Ok(self.frame_request(pdu))
+    }
+
+    /// Generate one RTU frame per chunk of a read larger than the spec limit
+    pub fn create_read_requests(&self) -> Result<Vec<Vec<u8>>, ModbusTransportError> {
+        let pdus = self.unit.create_read_requests()
+            .map_err(ModbusTransportError::Protocol)?;
+        self.record_pending_frames(pdus.iter().map(|pdu| PendingRequest {
+            function_code: pdu[0],
+            byte_count: self.unit.expected_byte_count(((pdu[3] as u16) << 8) | (pdu[4] as u16)),
+        }));
+        Ok(pdus.into_iter().map(|pdu| self.frame_request(pdu)).collect())
     }
 
     /// Generate complete RTU frame for write request
     pub fn create_write_request(&self) -> Result<Vec<u8>, ModbusTransportError> {
         let pdu = self.unit.get_write_request()
             .map_err(ModbusTransportError::Protocol)?;
-        Ok(self.wrap_rtu(pdu))
+        self.record_pending(pdu[0], None);
+        Ok(self.frame_request(pdu))
+    }
+
+    /// Build a write frame for `data` directly, forcing single or multi write
+    /// per `mode` instead of the builder's `force_multi_write`
+    pub fn create_write_request_using(&self, data: &[i32], mode: WriteMode) -> Result<Vec<u8>, ModbusTransportError> {
+        let values = data
+            .iter()
+            .enumerate()
+            .map(|(i, &val)| u16::try_from(val).map_err(|_| ModbusTransportError::ValueOverflow(val, i)))
+            .collect::<Result<Vec<u16>, _>>()?;
+        let pdu = self.unit.get_write_request_using(&values, mode)
+            .map_err(ModbusTransportError::Protocol)?;
+        self.record_pending(pdu[0], None);
+        Ok(self.frame_request(pdu))
+    }
+
+    /// Generate a fire-and-forget write frame: no response is awaited, so it
+    /// does not replace the pending request. Applies to the write function
+    /// codes 0x05, 0x06, 0x0F and 0x10; read requests always expect a response.
+    pub fn create_write_request_no_ack(&self) -> Result<Vec<u8>, ModbusTransportError> {
+        let pdu = self.unit.get_write_request()
+            .map_err(ModbusTransportError::Protocol)?;
+        Ok(self.frame_request(pdu))
+    }
+
+    /// Generate one frame per write PDU; several frames in sparse-write mode
+    pub fn create_write_requests(&self) -> Result<Vec<Vec<u8>>, ModbusTransportError> {
+        let pdus = self.unit.get_write_requests()
+            .map_err(ModbusTransportError::Protocol)?;
+        self.record_pending_frames(pdus.iter().map(|pdu| PendingRequest {
+            function_code: pdu[0],
+            byte_count: None,
+        }));
+        Ok(pdus.into_iter().map(|pdu| self.frame_request(pdu)).collect())
+    }
+
+    /// Frame a Write Single Register (0x06) request for the unit's start address.
+    /// The echoed address and value are verified by `parse_write_single_register_response`.
+    pub fn write_single_register(&self, value: i32) -> Result<Vec<u8>, ModbusTransportError> {
+        let pdu = self.unit.get_write_single_register(value)
+            .map_err(ModbusTransportError::Protocol)?;
+        self.record_pending(pdu[0], None);
+        Ok(self.frame_request(pdu))
+    }
+
+    /// Parse a Write Single Register (0x06) echo, see `ModbusUnit::parse_write_single_register_response`
+    pub fn parse_write_single_register_response(&self, frame: &[u8]) -> Result<(), ModbusTransportError> {
+        let pdu = self.unwrap_rtu(frame)?;
+        self.check_pending(&pdu)?;
+        self.unit.parse_write_single_register_response(&pdu)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    /// Build a write frame for `values` as floats, see `ModbusUnit::get_f32_write_request`
+    pub fn create_f32_write_request(&self, values: &[f32]) -> Result<Vec<u8>, ModbusTransportError> {
+        let pdu = self.unit.get_f32_write_request(values)
+            .map_err(ModbusTransportError::Protocol)?;
+        self.record_pending(pdu[0], None);
+        Ok(self.frame_request(pdu))
+    }
+
+    /// Parse a register response as floats, see `ModbusUnit::parse_as_f32`
+    pub fn parse_as_f32(&self, frame: &[u8]) -> Result<Vec<f32>, ModbusTransportError> {
+        let pdu = self.unwrap_rtu(frame)?;
+        self.check_pending(&pdu)?;
+        self.unit.parse_as_f32(&pdu)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    /// Build a write frame for `values` as u32, see `ModbusUnit::get_u32_write_request`
+    pub fn create_u32_write_request(&self, values: &[u32]) -> Result<Vec<u8>, ModbusTransportError> {
+        let pdu = self.unit.get_u32_write_request(values)
+            .map_err(ModbusTransportError::Protocol)?;
+        self.record_pending(pdu[0], None);
+        Ok(self.frame_request(pdu))
+    }
+
+    /// Parse a register response as u32, see `ModbusUnit::parse_as_u32`
+    pub fn parse_as_u32(&self, frame: &[u8]) -> Result<Vec<u32>, ModbusTransportError> {
+        let pdu = self.unwrap_rtu(frame)?;
+        self.check_pending(&pdu)?;
+        self.unit.parse_as_u32(&pdu)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    /// Build a write frame for `values` as i32, see `ModbusUnit::get_i32_write_request`
+    pub fn create_i32_write_request(&self, values: &[i32]) -> Result<Vec<u8>, ModbusTransportError> {
+        let pdu = self.unit.get_i32_write_request(values)
+            .map_err(ModbusTransportError::Protocol)?;
+        self.record_pending(pdu[0], None);
+        Ok(self.frame_request(pdu))
+    }
+
+    /// Parse a register response as i32, see `ModbusUnit::parse_as_i32`
+    pub fn parse_as_i32(&self, frame: &[u8]) -> Result<Vec<i32>, ModbusTransportError> {
+        let pdu = self.unwrap_rtu(frame)?;
+        self.check_pending(&pdu)?;
+        self.unit.parse_as_i32(&pdu)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    /// Build a write frame for `values` as u64, see `ModbusUnit::get_u64_write_request`
+    pub fn create_u64_write_request(&self, values: &[u64]) -> Result<Vec<u8>, ModbusTransportError> {
+        let pdu = self.unit.get_u64_write_request(values)
+            .map_err(ModbusTransportError::Protocol)?;
+        self.record_pending(pdu[0], None);
+        Ok(self.frame_request(pdu))
+    }
+
+    /// Parse a register response as u64, see `ModbusUnit::parse_as_u64`
+    pub fn parse_as_u64(&self, frame: &[u8]) -> Result<Vec<u64>, ModbusTransportError> {
+        let pdu = self.unwrap_rtu(frame)?;
+        self.check_pending(&pdu)?;
+        self.unit.parse_as_u64(&pdu)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    /// Build a write frame for `values` as i64, see `ModbusUnit::get_i64_write_request`
+    pub fn create_i64_write_request(&self, values: &[i64]) -> Result<Vec<u8>, ModbusTransportError> {
+        let pdu = self.unit.get_i64_write_request(values)
+            .map_err(ModbusTransportError::Protocol)?;
+        self.record_pending(pdu[0], None);
+        Ok(self.frame_request(pdu))
+    }
+
+    /// Parse a register response as i64, see `ModbusUnit::parse_as_i64`
+    pub fn parse_as_i64(&self, frame: &[u8]) -> Result<Vec<i64>, ModbusTransportError> {
+        let pdu = self.unwrap_rtu(frame)?;
+        self.check_pending(&pdu)?;
+        self.unit.parse_as_i64(&pdu)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    /// Build a write frame for `values` as f64, see `ModbusUnit::get_f64_write_request`
+    pub fn create_f64_write_request(&self, values: &[f64]) -> Result<Vec<u8>, ModbusTransportError> {
+        let pdu = self.unit.get_f64_write_request(values)
+            .map_err(ModbusTransportError::Protocol)?;
+        self.record_pending(pdu[0], None);
+        Ok(self.frame_request(pdu))
+    }
+
+    /// Parse a register response as f64, see `ModbusUnit::parse_as_f64`
+    pub fn parse_as_f64(&self, frame: &[u8]) -> Result<Vec<f64>, ModbusTransportError> {
+        let pdu = self.unwrap_rtu(frame)?;
+        self.check_pending(&pdu)?;
+        self.unit.parse_as_f64(&pdu)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    /// Build a write frame for `text`, see `ModbusUnit::get_string_write_request`
+    pub fn create_string_write_request(&self, text: &str) -> Result<Vec<u8>, ModbusTransportError> {
+        let pdu = self.unit.get_string_write_request(text)
+            .map_err(ModbusTransportError::Protocol)?;
+        self.record_pending(pdu[0], None);
+        Ok(self.frame_request(pdu))
+    }
+
+    /// Parse a register response as text, see `ModbusUnit::parse_as_string`
+    pub fn parse_as_string(&self, frame: &[u8]) -> Result<String, ModbusTransportError> {
+        let pdu = self.unwrap_rtu(frame)?;
+        self.check_pending(&pdu)?;
+        self.unit.parse_as_string(&pdu)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    /// Frame a Read FIFO Queue (0x18) request
+    pub fn read_fifo_queue(&self, fifo_pointer: u16) -> Result<Vec<u8>, ModbusTransportError> {
+        let pdu = self.unit.get_read_fifo_request(fifo_pointer);
+        self.record_pending(pdu[0], None);
+        Ok(self.frame_request(pdu))
+    }
+
+    /// Parse a Read FIFO Queue (0x18) response into the queued values
+    pub fn parse_fifo_response(&self, frame: &[u8]) -> Result<Vec<u16>, ModbusTransportError> {
+        let pdu = self.unwrap_rtu(frame)?;
+        self.check_pending(&pdu)?;
+        self.unit.parse_fifo_response(&pdu)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    /// Frame a Read Device Identification (0x2B / 0x0E) request
+    pub fn create_device_identification_request(&self, read_device_id_code: u8, object_id: u8) -> Result<Vec<u8>, ModbusTransportError> {
+        let pdu = self.unit.get_device_identification_request(read_device_id_code, object_id);
+        self.record_pending(pdu[0], None);
+        Ok(self.frame_request(pdu))
+    }
+
+    /// Parse a Read Device Identification (0x2B / 0x0E) response
+    pub fn parse_device_identification(&self, frame: &[u8]) -> Result<DeviceIdentification, ModbusTransportError> {
+        let pdu = self.unwrap_rtu(frame)?;
+        self.check_pending(&pdu)?;
+        self.unit.parse_device_identification(&pdu)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    /// Frame a Report Server ID (0x11) request
+    pub fn report_server_id(&self) -> Result<Vec<u8>, ModbusTransportError> {
+        let pdu = self.unit.get_report_server_id_request();
+        self.record_pending(pdu[0], None);
+        Ok(self.frame_request(pdu))
+    }
+
+    /// Parse a Report Server ID (0x11) response
+    pub fn parse_report_server_id(&self, frame: &[u8]) -> Result<ServerIdReport, ModbusTransportError> {
+        let pdu = self.unwrap_rtu(frame)?;
+        self.check_pending(&pdu)?;
+        self.unit.parse_report_server_id(&pdu)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    /// Frame a Diagnostics (0x08) request
+    pub fn create_diagnostics_request(&self, sub_function: u16, data: u16) -> Result<Vec<u8>, ModbusTransportError> {
+        let pdu = self.unit.get_diagnostics_request(sub_function, data);
+        self.record_pending(pdu[0], None);
+        Ok(self.frame_request(pdu))
+    }
+
+    /// Parse a Diagnostics (0x08) response into the echoed sub-function and data word
+    pub fn parse_diagnostics_response(&self, frame: &[u8]) -> Result<(u16, u16), ModbusTransportError> {
+        let pdu = self.unwrap_rtu(frame)?;
+        self.check_pending(&pdu)?;
+        self.unit.parse_diagnostics_response(&pdu)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    /// Check a Return Query Data loopback response echoes `sent`
+    pub fn check_loopback(&self, frame: &[u8], sent: u16) -> Result<bool, ModbusTransportError> {
+        let pdu = self.unwrap_rtu(frame)?;
+        self.check_pending(&pdu)?;
+        self.unit.check_loopback(&pdu, sent)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    /// Frame a Read Exception Status (0x07) request
+    pub fn read_exception_status(&self) -> Result<Vec<u8>, ModbusTransportError> {
+        let pdu = self.unit.get_read_exception_status_request();
+        self.record_pending(pdu[0], None);
+        Ok(self.frame_request(pdu))
+    }
+
+    /// Parse a Read Exception Status (0x07) response into the status byte
+    pub fn parse_exception_status(&self, frame: &[u8]) -> Result<u8, ModbusTransportError> {
+        let pdu = self.unwrap_rtu(frame)?;
+        self.check_pending(&pdu)?;
+        self.unit.parse_exception_status(&pdu)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    /// Frame a Mask Write Register (0x16) request for the unit's start address.
+    /// The echo is verified by `parse_mask_write_response`.
+    pub fn create_mask_write_request(&self, and_mask: u16, or_mask: u16) -> Result<Vec<u8>, ModbusTransportError> {
+        let pdu = self.unit.get_mask_write_request(and_mask, or_mask)
+            .map_err(ModbusTransportError::Protocol)?;
+        self.record_pending(pdu[0], None);
+        Ok(self.frame_request(pdu))
+    }
+
+    /// Frame a Read/Write Multiple Registers (0x17) request: read `read_qty`
+    /// registers at `read_addr` and write `write_data` at the unit's start address.
+    /// The response is decoded by `parse_read_write_response`.
+    pub fn create_read_write_request(&self, read_addr: u16, read_qty: u16, write_data: &[i32]) -> Result<Vec<u8>, ModbusTransportError> {
+        let pdu = self.unit.get_read_write_request(read_addr, read_qty, write_data)
+            .map_err(ModbusTransportError::Protocol)?;
+        self.record_pending(pdu[0], Some((read_qty * 2) as u8));
+        Ok(self.frame_request(pdu))
+    }
+
+    /// Parse a Mask Write Register (0x16) echo, see `ModbusUnit::parse_mask_write_response`
+    pub fn parse_mask_write_response(&self, frame: &[u8]) -> Result<(), ModbusTransportError> {
+        let pdu = self.unwrap_rtu(frame)?;
+        self.check_pending(&pdu)?;
+        self.unit.parse_mask_write_response(&pdu)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    /// Parse a Read/Write Multiple Registers (0x17) response into the read values
+    pub fn parse_read_write_response(&self, frame: &[u8]) -> Result<Vec<u16>, ModbusTransportError> {
+        let pdu = self.unwrap_rtu(frame)?;
+        self.check_pending(&pdu)?;
+        self.unit.parse_read_write_response(&pdu)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    /// Frame a caller-built PDU, e.g. a vendor-specific function.
+    /// The PDU is not validated.
+    pub fn create_raw_request(&self, pdu: &[u8]) -> Vec<u8> {
+        self.record_pending_frames(pdu.first().map(|&function_code| PendingRequest {
+            function_code,
+            byte_count: None,
+        }));
+        self.frame_request(pdu.to_vec())
+    }
+
+    /// Validate the framing (unit id and CRC) and return the inner PDU
+    /// without any function-specific parsing
+    pub fn unwrap_pdu(&self, frame: &[u8]) -> Result<Vec<u8>, ModbusTransportError> {
+        self.unwrap_rtu(frame)
+    }
+
+    /// Encode `value` into two registers in `order`, stage them and build the
+    /// multi-write frame. The unit must be configured with `length == 2`.
+    pub fn write_f32(&self, value: f32, order: WordOrder) -> Result<Vec<u8>, ModbusTransportError> {
+        if self.unit.length() != 2 {
+            return Err(ModbusUnitError::InvalidUnitLength {
+                expected: 2,
+                actual: self.unit.length(),
+            }.into());
+        }
+        for (index, register) in f32_to_registers(value, order).into_iter().enumerate() {
+            self.unit.set(index, register)?;
+        }
+        self.create_write_request()
+    }
+
+    /// Check a read-back of the written range against the staged write values
+    pub fn verify_write(&self, frame: &[u8]) -> Result<(), ModbusTransportError> {
+        let pdu = self.unwrap_rtu(frame)?;
+        self.check_pending(&pdu)?;
+        self.unit.verify_write(&pdu)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    /// Stage `text` across the unit's registers, padded with the configured pad byte
+    pub fn set_string(&self, text: &str) -> Result<(), ModbusTransportError> {
+        self.unit.set_string(text)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    pub fn clear_staging(&self) {
+        self.unit.clear_staging();
+    }
+
+    pub fn is_staging_complete(&self) -> bool {
+        self.unit.is_staging_complete()
     }
 
     /// Parse RTU response and extract values
     pub fn parse_response(&self, frame: &[u8]) -> Result<(), ModbusTransportError> {
         let pdu = self.unwrap_rtu(frame)?;
+        self.check_pending(&pdu)?;
         self.unit.parse_response(&pdu)
             .map_err(ModbusTransportError::Protocol)
     }
 
+    /// Parse two RTU responses to the same read, accepting the values only if both agree
+    pub fn parse_response_verified(&self, first: &[u8], second: &[u8]) -> Result<Vec<u16>, ModbusTransportError> {
+        let first_pdu = self.unwrap_rtu(first)?;
+        let second_pdu = self.unwrap_rtu(second)?;
+        // Both responses answer the latest read, so match each against it
+        let latest = self.pending.borrow().front().copied();
+        if let Some(latest) = latest {
+            self.pending.borrow_mut().push_front(latest);
+        }
+        self.check_pending(&first_pdu)?;
+        self.check_pending(&second_pdu)?;
+        self.unit.parse_response_verified(&first_pdu, &second_pdu)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    /// Parse a response, store the values on the unit and return them, see `last_values`
+    pub fn parse_response_cached(&self, frame: &[u8]) -> Result<Vec<u16>, ModbusTransportError> {
+        let pdu = self.unwrap_rtu(frame)?;
+        self.check_pending(&pdu)?;
+        self.unit.parse_response_cached(&pdu)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    pub fn last_values(&self) -> Option<Vec<u16>> {
+        self.unit.last_values()
+    }
+
+    pub fn last_updated(&self) -> Option<Instant> {
+        self.unit.last_updated()
+    }
+
+    /// Parse the response to `create_read_request_count`
+    pub fn parse_response_count(&self, frame: &[u8], count: u16) -> Result<Vec<u16>, ModbusTransportError> {
+        let pdu = self.unwrap_rtu(frame)?;
+        self.check_pending(&pdu)?;
+        self.unit.parse_response_count(&pdu, count)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    /// Parse the response to `create_probe_request`, true if the device answered normally
+    pub fn parse_probe_response(&self, frame: &[u8]) -> Result<bool, ModbusTransportError> {
+        let pdu = self.unwrap_rtu(frame)?;
+        self.check_pending(&pdu)?;
+        self.unit.parse_probe_response(&pdu)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    /// Parse the responses to `create_read_requests`, in order
+    pub fn parse_responses(&self, frames: &[Vec<u8>]) -> Result<(), ModbusTransportError> {
+        let pdus = frames
+            .iter()
+            .map(|frame| {
+                let pdu = self.unwrap_rtu(frame)?;
+                self.check_pending(&pdu)?;
+                Ok(pdu)
+            })
+            .collect::<Result<Vec<_>, ModbusTransportError>>()?;
+        self.unit.parse_responses(&pdus)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    /// Parse the responses to `create_read_requests` up to the first failing
+    /// frame, returning the values before it and the index and error where it stopped
+    pub fn parse_responses_partial(&self, frames: &[Vec<u8>]) -> (Vec<u16>, Option<(usize, ModbusTransportError)>) {
+        let mut pdus = Vec::with_capacity(frames.len());
+        let mut unwrap_failure = None;
+        for (index, frame) in frames.iter().enumerate() {
+            match self.unwrap_rtu(frame).and_then(|pdu| self.check_pending(&pdu).map(|_| pdu)) {
+                Ok(pdu) => pdus.push(pdu),
+                Err(err) => {
+                    unwrap_failure = Some((index, err));
+                    break;
+                }
+            }
+        }
+
+        let (values, unit_failure) = self.unit.parse_responses_partial(&pdus);
+        let failure = match (unit_failure, unwrap_failure) {
+            (Some((index, err)), Some((unwrap_index, _))) if index < unwrap_index => {
+                Some((index, ModbusTransportError::Protocol(err)))
+            }
+            (_, Some(unwrap_failure)) => Some(unwrap_failure),
+            (unit_failure, None) => unit_failure.map(|(index, err)| (index, ModbusTransportError::Protocol(err))),
+        };
+        (values, failure)
+    }
+
+    /// Parse RTU write acknowledgement
+    pub fn parse_write_response(&self, frame: &[u8]) -> Result<(), ModbusTransportError> {
+        let pdu = self.unwrap_rtu(frame)?;
+        self.check_pending(&pdu)?;
+        self.unit.parse_write_response(&pdu)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    /// Parse the acknowledgements to the frames from `create_write_requests`, in order
+    pub fn parse_write_responses(&self, requests: &[Vec<u8>], frames: &[Vec<u8>]) -> Result<(), ModbusTransportError> {
+        if requests.len() != frames.len() {
+            return Err(ModbusUnitError::ResponseCountMismatch {
+                expected: requests.len(),
+                actual: frames.len(),
+            }.into());
+        }
+        for (request, frame) in requests.iter().zip(frames) {
+            // Device id, function code, echoed address and CRC
+            if request.len() < 6 {
+                return Err(ModbusTransportError::FrameTooShort);
+            }
+            let pdu = self.unwrap_rtu(frame)?;
+            self.check_pending(&pdu)?;
+            self.unit.check_write_ack(&pdu, &request[1..request.len() - 2])?;
+        }
+        Ok(())
+    }
+
+    /// Parse RTU write-multiple acknowledgement, returning the echoed quantity
+    pub fn parse_multi_write_response(&self, frame: &[u8]) -> Result<u16, ModbusTransportError> {
+        let pdu = self.unwrap_rtu(frame)?;
+        self.check_pending(&pdu)?;
+        self.unit.parse_multi_write_response(&pdu)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    /// Parse RTU Write Single Coil echo, returning the echoed coil state
+    pub fn parse_single_coil_write_response(&self, frame: &[u8]) -> Result<bool, ModbusTransportError> {
+        let pdu = self.unwrap_rtu(frame)?;
+        self.check_pending(&pdu)?;
+        self.unit.parse_single_coil_write_response(&pdu)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    fn record_pending(&self, function_code: u8, byte_count: Option<u8>) {
+        self.record_pending_frames([PendingRequest { function_code, byte_count }]);
+    }
+
+    /// Replace the outstanding requests with those of the frames just built
+    fn record_pending_frames(&self, requests: impl IntoIterator<Item = PendingRequest>) {
+        if self.match_responses {
+            *self.pending.borrow_mut() = requests.into_iter().collect();
+        }
+    }
+
+    /// Reject responses that cannot belong to the oldest outstanding request.
+    /// The request is consumed, so a duplicate response is rejected as well.
+    fn check_pending(&self, pdu: &[u8]) -> Result<(), ModbusTransportError> {
+        if !self.match_responses {
+            return Ok(());
+        }
+        let pending = self.pending
+            .borrow_mut()
+            .pop_front()
+            .ok_or(ModbusTransportError::UnsolicitedResponse)?;
+
+        let function_code = pdu.first().copied().unwrap_or(0) & !function_code::EXCEPTION_OFFSET;
+        if function_code != pending.function_code {
+            return Err(ModbusTransportError::ResponseDoesNotMatchRequest {
+                field: "function code",
+                expected: pending.function_code,
+                received: function_code,
+            });
+        }
+
+        let is_exception = pdu[0] & function_code::EXCEPTION_OFFSET != 0;
+        if let (Some(expected), Some(&received), false) = (pending.byte_count, pdu.get(1), is_exception)
+            && received != expected
+        {
+            return Err(ModbusTransportError::ResponseDoesNotMatchRequest {
+                field: "byte count",
+                expected,
+                received,
+            });
+        }
+        Ok(())
+    }
+
+    /// Frame a request PDU once the bus is ready, see `with_min_interval`
+    fn frame_request(&self, pdu: Vec<u8>) -> Vec<u8> {
+        self.wait_for_bus();
+        self.wrap_rtu(pdu)
+    }
+
     fn wrap_rtu(&self, pdu: Vec<u8>) -> Vec<u8> {
         let mut frame = Vec::with_capacity(1 + pdu.len() + 2);
         frame.push(self.device_id);
         frame.extend(&pdu);
 
-        let crc = Self::calculate_crc(&frame);
+        let crc = if self.defer_crc { 0x0000 } else { Self::calculate_crc(&frame) };
         frame.push(crc as u8);
         frame.push((crc >> 8) as u8);
 
@@ -115,6 +699,36 @@ impl ModbusRTU {
             });
         }
 
+        Self::check_crc(frame)?;
+
+        let pdu_start = 1 + self.skip_leading_bytes;
+        if frame.len() < pdu_start + 3 {
+            return Err(ModbusTransportError::FrameTooShort);
+        }
+
+        // Only a frame addressed to us with a valid CRC ends the transaction
+        self.transaction_finished();
+        Ok(frame[pdu_start..frame.len() - 2].to_vec())
+    }
+
+    /// Overwrite the trailing two bytes of `frame` with the CRC of the rest
+    pub fn finalize_frame(frame: &mut [u8]) -> Result<(), ModbusTransportError> {
+        if frame.len() < 4 {
+            return Err(ModbusTransportError::FrameTooShort);
+        }
+        let body_len = frame.len() - 2;
+        let crc = Self::calculate_crc(&frame[..body_len]);
+        frame[body_len] = crc as u8;
+        frame[body_len + 1] = (crc >> 8) as u8;
+        Ok(())
+    }
+
+    /// Validate the trailing CRC of a complete RTU frame
+    pub(crate) fn check_crc(frame: &[u8]) -> Result<(), ModbusTransportError> {
+        if frame.len() < 4 {
+            return Err(ModbusTransportError::FrameTooShort);
+        }
+
         let received_crc = (frame[frame.len() - 1] as u16) << 8 | frame[frame.len() - 2] as u16;
         let calculated_crc = Self::calculate_crc(&frame[..frame.len() - 2]);
 
@@ -124,22 +738,360 @@ impl ModbusRTU {
                 received: received_crc,
             });
         }
+        Ok(())
+    }
 
-        Ok(frame[1..frame.len() - 2].to_vec())
+    /// Record that the current transaction has finished on the bus. Parsing a
+    /// well-framed response does this already; call it when a request gets no
+    /// response or only a corrupt one.
+    pub fn transaction_finished(&self) {
+        self.last_transaction_end.set(Some(Instant::now()));
+    }
+
+    /// Exact length of the RTU frame answering `create_read_request`:
+    /// unit id, PDU and CRC, plus any `skip_leading_bytes`
+    pub fn expected_response_frame_len(&self) -> usize {
+        1 + self.skip_leading_bytes + self.unit.expected_response_pdu_len() + 2
+    }
+
+    /// Error for a read that timed out after `received` bytes, carrying
+    /// `expected_response_frame_len` so a silent device can be told apart
+    /// from a slow or truncated one
+    pub fn read_timeout(&self, received: usize) -> ModbusTransportError {
+        ModbusTransportError::ReadTimeout {
+            received,
+            expected: self.expected_response_frame_len(),
+        }
+    }
+
+    /// Remaining quiet time required by `with_min_interval` at `now`
+    pub fn time_until_ready(&self, now: Instant) -> Duration {
+        match (self.min_interval, self.last_transaction_end.get()) {
+            (Some(interval), Some(end)) => (end + interval).saturating_duration_since(now),
+            _ => Duration::ZERO,
+        }
+    }
+
+    /// Sleep until the minimum interval since the last transaction has elapsed
+    pub fn wait_for_bus(&self) {
+        let remaining = self.time_until_ready(Instant::now());
+        if !remaining.is_zero() {
+            std::thread::sleep(remaining);
+        }
+    }
+
+    /// Time to transmit `frame_len` bytes at `baud` plus the 3.5 character
+    /// inter-frame gap. Each character is 11 bits on the wire (start, 8 data,
+    /// parity or second stop, stop). Above 19200 baud the spec fixes the gap
+    /// at 1.75 ms.
+    pub fn frame_transmission_time(frame_len: usize, baud: u32) -> Duration {
+        let baud = baud.max(1) as u128;
+        let frame_nanos = frame_len as u128 * 11 * 1_000_000_000 / baud;
+        let gap_nanos = if baud > 19_200 {
+            1_750_000
+        } else {
+            35 * 11 * 1_000_000_000 / (10 * baud)
+        };
+        Duration::from_nanos((frame_nanos + gap_nanos) as u64)
     }
 
     fn calculate_crc(data: &[u8]) -> u16 {
-        let mut crc: u16 = 0xFFFF;
-        for &byte in data {
-            crc ^= byte as u16;
+        let mut crc = Crc16::new();
+        crc.update(data);
+        crc.finalize()
+    }
+}
+
+/// Build a complete RTU read frame from loose parameters in one shot
+pub fn build_rtu_read(device_id: u8, register_type: RegisterType, addr: u16, len: u16) -> Result<Vec<u8>, ModbusTransportError> {
+    ModbusRTU::from_parts(addr, len, register_type, device_id)?
+        .create_read_request()
+}
+
+/// Incremental Modbus CRC-16, for frames that arrive in chunks
+#[derive(Copy, Clone, Debug)]
+pub struct Crc16 {
+    crc: u16,
+}
+
+impl Default for Crc16 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Crc16 {
+    pub fn new() -> Self {
+        Crc16 { crc: 0xFFFF }
+    }
+
+    pub fn update(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.crc ^= byte as u16;
             for _ in 0..8 {
-                if (crc & 0x0001) != 0 {
-                    crc = (crc >> 1) ^ 0xA001;
+                if (self.crc & 0x0001) != 0 {
+                    self.crc = (self.crc >> 1) ^ 0xA001;
                 } else {
-                    crc >>= 1;
+                    self.crc >>= 1;
                 }
             }
         }
-        crc
     }
-}
\ No newline at end of file
+
+    pub fn finalize(self) -> u16 {
+        self.crc
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matched_unit(register_type: RegisterType, length: i32) -> ModbusRTUBuilder {
+        ModbusRTU::builder()
+            .address(0)
+            .length(length)
+            .register_type(register_type)
+            .device_id(1)
+            .match_responses(true)
+    }
+
+    #[test]
+    fn sparse_write_acks_match_every_frame() {
+        let unit = matched_unit(RegisterType::HoldingRegister, 4)
+            .sparse_writes(true)
+            .build()
+            .unwrap();
+        unit.unit.set(0, 10).unwrap();
+        unit.unit.set(2, 30).unwrap();
+
+        let requests = unit.create_write_requests().unwrap();
+        assert_eq!(requests.len(), 2);
+        // A single write is acknowledged by echoing the request
+        assert!(unit.parse_write_responses(&requests, &requests).is_ok());
+        assert!(matches!(
+            unit.parse_write_response(&requests[0]),
+            Err(ModbusTransportError::UnsolicitedResponse)
+        ));
+    }
+
+    #[test]
+    fn raw_request_is_matched() {
+        let unit = matched_unit(RegisterType::HoldingRegister, 1).build().unwrap();
+        unit.create_raw_request(&[function_code::READ_HOLDING_REGISTERS, 0x00, 0x00, 0x00, 0x01]);
+
+        let coil_response = unit.wrap_rtu(vec![function_code::READ_COILS, 0x01, 0x01]);
+        assert!(matches!(
+            unit.parse_response(&coil_response),
+            Err(ModbusTransportError::ResponseDoesNotMatchRequest { field: "function code", .. })
+        ));
+
+        unit.create_raw_request(&[function_code::READ_HOLDING_REGISTERS, 0x00, 0x00, 0x00, 0x01]);
+        let response = unit.wrap_rtu(vec![function_code::READ_HOLDING_REGISTERS, 0x02, 0x12, 0x34]);
+        assert!(unit.parse_response(&response).is_ok());
+    }
+
+    #[test]
+    fn single_register_echo_is_consumed() {
+        let unit = ModbusRTU::builder()
+            .address(0)
+            .length(1)
+            .register_type(RegisterType::HoldingRegister)
+            .device_id(1)
+            .build()
+            .unwrap();
+        let request = unit.write_single_register(0x1234).unwrap();
+        unit.parse_write_single_register_response(&request).unwrap();
+        assert!(matches!(
+            unit.parse_write_single_register_response(&request),
+            Err(ModbusTransportError::Protocol(ModbusUnitError::NoPendingRequest(0x06)))
+        ));
+
+        unit.write_single_register(0x1234).unwrap();
+        let echo = unit.wrap_rtu(vec![0x06, 0x00, 0x00, 0x12, 0x35]);
+        assert!(matches!(
+            unit.parse_write_single_register_response(&echo),
+            Err(ModbusTransportError::Protocol(ModbusUnitError::ValueEchoMismatch { expected: 0x1234, received: 0x1235 }))
+        ));
+    }
+
+    #[test]
+    fn single_register_accepts_the_same_range_as_set() {
+        let unit = matched_unit(RegisterType::HoldingRegister, 1).build().unwrap();
+        assert!(unit.write_single_register(65535).is_ok());
+        assert!(matches!(
+            unit.write_single_register(-1),
+            Err(ModbusTransportError::Protocol(ModbusUnitError::ValueOverflow(-1, 0)))
+        ));
+        assert!(matches!(
+            unit.create_write_request_using(&[-1], WriteMode::ForceSingle),
+            Err(ModbusTransportError::ValueOverflow(-1, 0))
+        ));
+    }
+
+    #[test]
+    fn parsing_a_response_starts_the_min_interval() {
+        let unit = matched_unit(RegisterType::HoldingRegister, 1)
+            .with_min_interval(Duration::from_secs(60))
+            .build()
+            .unwrap();
+        assert_eq!(unit.time_until_ready(Instant::now()), Duration::ZERO);
+
+        unit.create_read_request().unwrap();
+        let response = unit.wrap_rtu(vec![function_code::READ_HOLDING_REGISTERS, 0x02, 0x00, 0x01]);
+        unit.parse_response(&response).unwrap();
+        assert!(unit.time_until_ready(Instant::now()) > Duration::from_secs(59));
+    }
+
+    #[test]
+    fn deferred_crc_is_stamped_by_finalize_frame() {
+        let builder = || {
+            ModbusRTU::builder()
+                .address(0)
+                .length(2)
+                .register_type(RegisterType::HoldingRegister)
+                .device_id(1)
+        };
+        let normal = builder().build().unwrap().create_read_request().unwrap();
+        let mut deferred = builder().defer_crc(true).build().unwrap().create_read_request().unwrap();
+        assert_eq!(&deferred[deferred.len() - 2..], &[0x00, 0x00]);
+
+        ModbusRTU::finalize_frame(&mut deferred).unwrap();
+        assert_eq!(deferred, normal);
+        assert!(ModbusRTU::check_crc(&deferred).is_ok());
+    }
+
+    #[test]
+    fn no_ack_write_keeps_the_pending_read() {
+        let unit = matched_unit(RegisterType::HoldingRegister, 1).build().unwrap();
+        unit.create_read_request().unwrap();
+        unit.unit.set(0, 5).unwrap();
+        unit.create_write_request_no_ack().unwrap();
+
+        let response = unit.wrap_rtu(vec![function_code::READ_HOLDING_REGISTERS, 0x02, 0x00, 0x07]);
+        unit.parse_response(&response).unwrap();
+    }
+
+    #[test]
+    fn truncated_write_requests_are_rejected_without_panicking() {
+        let unit = matched_unit(RegisterType::HoldingRegister, 2)
+            .sparse_writes(true)
+            .build()
+            .unwrap();
+        unit.unit.set(0, 1).unwrap();
+        let requests = unit.create_write_requests().unwrap();
+        for len in 4..6 {
+            let truncated = vec![requests[0][..len].to_vec()];
+            assert!(matches!(
+                unit.parse_write_responses(&truncated, &requests),
+                Err(ModbusTransportError::FrameTooShort)
+            ));
+        }
+    }
+
+    #[test]
+    fn chunked_reads_match_each_frame() {
+        let unit = matched_unit(RegisterType::HoldingRegister, 130).build().unwrap();
+        let requests = unit.create_read_requests().unwrap();
+        assert_eq!(requests.len(), 2);
+        let respond = |quantity: u8| {
+            let mut pdu = vec![function_code::READ_HOLDING_REGISTERS, quantity * 2];
+            pdu.extend(vec![0; quantity as usize * 2]);
+            unit.wrap_rtu(pdu)
+        };
+
+        assert!(unit.parse_responses(&[respond(125), respond(5)]).is_ok());
+
+        unit.create_read_requests().unwrap();
+        assert!(matches!(
+            unit.parse_responses(&[respond(125), respond(4)]),
+            Err(ModbusTransportError::ResponseDoesNotMatchRequest { field: "byte count", expected: 10, received: 8 })
+        ));
+
+        unit.create_read_requests().unwrap();
+        let (values, failure) = unit.parse_responses_partial(&[respond(5), respond(5)]);
+        assert!(values.is_empty());
+        assert!(matches!(failure, Some((0, ModbusTransportError::ResponseDoesNotMatchRequest { field: "byte count", .. }))));
+    }
+
+    #[test]
+    fn stale_responses_are_rejected() {
+        let unit = matched_unit(RegisterType::HoldingRegister, 2).build().unwrap();
+        unit.create_read_request_count(1).unwrap();
+        let full = unit.wrap_rtu(vec![function_code::READ_HOLDING_REGISTERS, 0x04, 0, 1, 0, 2]);
+        assert!(matches!(
+            unit.parse_response_count(&full, 1),
+            Err(ModbusTransportError::ResponseDoesNotMatchRequest { field: "byte count", expected: 2, received: 4 })
+        ));
+
+        unit.create_read_request().unwrap();
+        unit.parse_response(&full).unwrap();
+        assert!(matches!(unit.parse_response(&full), Err(ModbusTransportError::UnsolicitedResponse)));
+        assert!(matches!(unit.verify_write(&full), Err(ModbusTransportError::UnsolicitedResponse)));
+    }
+
+    #[test]
+    fn write_f32_stages_both_registers() {
+        let unit = matched_unit(RegisterType::HoldingRegister, 2).build().unwrap();
+        let request = unit.write_f32(1.5, WordOrder::LowWordFirst).unwrap();
+        // 1.5 is 0x3FC0_0000, low word first
+        assert_eq!(&request[1..11], &[0x10, 0x00, 0x00, 0x00, 0x02, 0x04, 0x00, 0x00, 0x3F, 0xC0]);
+
+        unit.create_read_request().unwrap();
+        let readback = unit.wrap_rtu(vec![function_code::READ_HOLDING_REGISTERS, 0x04, 0x00, 0x00, 0x3F, 0xC0]);
+        assert!(unit.verify_write(&readback).is_ok());
+    }
+
+    #[test]
+    fn crc16_chunks_match_one_shot_crc() {
+        let frame = [0x01, 0x03, 0x00, 0x00, 0x00, 0x01];
+        assert_eq!(ModbusRTU::calculate_crc(&frame), 0x0A84);
+
+        for split in 0..=frame.len() {
+            let mut crc = Crc16::new();
+            crc.update(&frame[..split]);
+            crc.update(&frame[split..]);
+            assert_eq!(crc.finalize(), 0x0A84, "split at {split}");
+        }
+
+        let mut bytewise = Crc16::default();
+        for byte in frame {
+            bytewise.update(&[byte]);
+        }
+        assert_eq!(bytewise.finalize(), 0x0A84);
+    }
+
+    #[test]
+    fn request_builders_wait_out_the_min_interval() {
+        let unit = matched_unit(RegisterType::HoldingRegister, 1)
+            .with_min_interval(Duration::from_millis(50))
+            .build()
+            .unwrap();
+        unit.create_read_request().unwrap();
+        let response = unit.wrap_rtu(vec![function_code::READ_HOLDING_REGISTERS, 0x02, 0x00, 0x01]);
+        unit.parse_response(&response).unwrap();
+
+        let started = Instant::now();
+        unit.create_read_request().unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(45));
+        assert_eq!(unit.time_until_ready(Instant::now()), Duration::ZERO);
+    }
+
+    #[test]
+    fn rejected_frames_do_not_end_the_transaction() {
+        let unit = matched_unit(RegisterType::HoldingRegister, 1)
+            .with_min_interval(Duration::from_secs(60))
+            .build()
+            .unwrap();
+        unit.create_read_request().unwrap();
+        let mut corrupt = unit.wrap_rtu(vec![function_code::READ_HOLDING_REGISTERS, 0x02, 0x00, 0x01]);
+        let last = corrupt.len() - 1;
+        corrupt[last] ^= 0xFF;
+        assert!(matches!(
+            unit.parse_response(&corrupt),
+            Err(ModbusTransportError::CrcMismatch { .. })
+        ));
+        assert!(unit.parse_response(&[0x02, 0x03, 0x02, 0x00, 0x01, 0x00, 0x00]).is_err());
+        assert_eq!(unit.time_until_ready(Instant::now()), Duration::ZERO);
+    }
+}
@@ -1,55 +1,84 @@
+use std::cell::RefCell;
+use std::time::Instant;
 use super::*;
+
 pub struct ModbusTCPUnitBuilder {
     unit_builder: ModbusUnitBuilder,
     device_id: Option<u8>,
+    validate_unit_id: bool,
+    mbap_length_endianness: Endianness,
+    fixed_transaction_id: Option<u16>,
+    max_outstanding: Option<usize>,
+    allowed_function_codes: Vec<u8>,
+    validate_transaction_id: bool,
 }
 
 impl ModbusTCPUnitBuilder {
-    pub fn address(mut self, addr: i32) -> Self {
-        self.unit_builder.address(addr);
+    forward_unit_builder!();
+
+    pub fn device_id(mut self, device_id: u8) -> Self {
+        self.device_id = Some(device_id);
         self
     }
 
-    pub fn length(mut self, length: i32) -> Self {
-        self.unit_builder.length(length);
+    /// Set to false for servers that don't populate the MBAP unit ID meaningfully
+    pub fn validate_unit_id(mut self, validate_unit_id: bool) -> Self {
+        self.validate_unit_id = validate_unit_id;
         self
     }
 
-    pub fn register_type(mut self, register_type: RegisterType) -> Self {
-        self.unit_builder.register_type(register_type);
+    /// Byte order of the MBAP length field. Big-endian per spec; little-endian
+    /// for gateways that get it wrong.
+    pub fn mbap_length_endianness(mut self, endianness: Endianness) -> Self {
+        self.mbap_length_endianness = endianness;
         self
     }
 
-    pub fn with_read_cmd(mut self, spec_read_cmd: i32) -> Self {
-        self.unit_builder.with_read_cmd(spec_read_cmd);
+    /// Use the same transaction ID for every frame instead of incrementing it,
+    /// which keeps captures stable. Responses can then no longer be told apart
+    /// by transaction ID, so don't pipeline requests in this mode.
+    pub fn fixed_transaction_id(mut self, transaction_id: u16) -> Self {
+        self.fixed_transaction_id = Some(transaction_id);
         self
     }
 
-    pub fn with_write_cmd(mut self, spec_write_cmd: i32) -> Self {
-        self.unit_builder.with_write_cmd(spec_write_cmd);
+    /// Cap on requests awaiting a response when pipelining. Frames beyond the
+    /// limit are refused with `TooManyOutstanding` until a response arrives.
+    pub fn max_outstanding(mut self, max_outstanding: usize) -> Self {
+        self.max_outstanding = Some(max_outstanding);
         self
     }
 
-    pub fn with_multi_write_cmd(mut self, multi_write_cmd: i32) -> Self {
-        self.unit_builder.with_multi_write_cmd(multi_write_cmd);
+    /// Reject responses whose function code (ignoring the exception bit) is
+    /// not listed, before any decoding. Empty allows all (default).
+    pub fn allowed_function_codes(mut self, function_codes: &[u8]) -> Self {
+        self.allowed_function_codes = function_codes.to_vec();
         self
     }
 
-    pub fn device_id(mut self, device_id: u8) -> Self {
-        self.device_id = Some(device_id);
+    /// Reject responses whose transaction ID doesn't belong to the latest
+    /// request (default). Set to false when matching responses externally.
+    pub fn validate_transaction_id(mut self, validate_transaction_id: bool) -> Self {
+        self.validate_transaction_id = validate_transaction_id;
         self
     }
 
     pub fn build(self) -> Result<ModbusTCPUnit, ModbusTransportError> {
-        let unit = self.unit_builder.build()
-            .map_err(ModbusTransportError::Protocol)?;
-
-        let device_id = self.device_id.ok_or(ModbusTransportError::DeviceIdMissing)?;
+        let (unit, device_id) = build_unit(self.unit_builder, self.device_id)?;
 
         Ok(ModbusTCPUnit {
             unit,
             transaction_id: 0,
             device_id,
+            validate_unit_id: self.validate_unit_id,
+            mbap_length_endianness: self.mbap_length_endianness,
+            fixed_transaction_id: self.fixed_transaction_id,
+            max_outstanding: self.max_outstanding,
+            outstanding: RefCell::new(Vec::new()),
+            allowed_function_codes: self.allowed_function_codes,
+            validate_transaction_id: self.validate_transaction_id,
+            recent_read_ids: [None; 2],
+            read_batch_ids: Vec::new(),
         })
     }
 }
@@ -59,6 +88,17 @@ pub struct ModbusTCPUnit {
     unit: ModbusUnit,
     transaction_id: u16,
     device_id: u8,
+    validate_unit_id: bool,
+    mbap_length_endianness: Endianness,
+    fixed_transaction_id: Option<u16>,
+    max_outstanding: Option<usize>,
+    outstanding: RefCell<Vec<u16>>,
+    allowed_function_codes: Vec<u8>,
+    validate_transaction_id: bool,
+    /// Transaction IDs of the two latest `create_read_request` frames, oldest first
+    recent_read_ids: [Option<u16>; 2],
+    /// Transaction IDs of the frames from the latest `create_read_requests`
+    read_batch_ids: Vec<u16>,
 }
 
 impl ModbusTCPUnit {
@@ -67,74 +107,660 @@ impl ModbusTCPUnit {
         ModbusTCPUnitBuilder {
             unit_builder: ModbusUnit::builder(),
             device_id: None,
+            validate_unit_id: true,
+            mbap_length_endianness: Endianness::Big,
+            fixed_transaction_id: None,
+            max_outstanding: None,
+            allowed_function_codes: Vec::new(),
+            validate_transaction_id: true,
         }
     }
 
+    /// Flat constructor for FFI callers, validated exactly like `build()`
+    pub fn from_parts(start_addr: u16, length: u16, register_type: RegisterType, device_id: u8) -> Result<ModbusTCPUnit, ModbusTransportError> {
+        Self::builder()
+            .address(start_addr as i32)
+            .length(length as i32)
+            .register_type(register_type)
+            .device_id(device_id)
+            .build()
+    }
+
     /// Generate complete TCP frame for read request
     pub fn create_read_request(&mut self) -> Result<Vec<u8>, ModbusTransportError> {
+        self.check_outstanding(1)?;
         let pdu = self.unit.create_read_request()
             .map_err(ModbusTransportError::Protocol)?;
+        let frame = self.wrap_tcp(pdu);
+        self.recent_read_ids = [self.recent_read_ids[1], Some(self.transaction_id)];
+        Ok(frame)
+    }
+
+    /// Read request frame for only the first `count` values of the unit
+    pub fn create_read_request_count(&mut self, count: u16) -> Result<Vec<u8>, ModbusTransportError> {
+        self.check_outstanding(1)?;
+        let pdu = self.unit.create_read_request_count(count)
+            .map_err(ModbusTransportError::Protocol)?;
         Ok(self.wrap_tcp(pdu))
     }
 
+    /// Frame for a one-value read at the unit's start address, for health checks
+    pub fn create_probe_request(&mut self) -> Result<Vec<u8>, ModbusTransportError> {
+        self.check_outstanding(1)?;
+        let pdu = self.unit.create_probe_request()
+            .map_err(ModbusTransportError::Protocol)?;
+        Ok(self.wrap_tcp(pdu))
+    }
+
+    /// Generate one TCP frame per chunk of a read larger than the spec limit
+    pub fn create_read_requests(&mut self) -> Result<Vec<Vec<u8>>, ModbusTransportError> {
+        let pdus = self.unit.create_read_requests()
+            .map_err(ModbusTransportError::Protocol)?;
+        self.check_outstanding(pdus.len())?;
+        self.read_batch_ids.clear();
+        let mut frames = Vec::with_capacity(pdus.len());
+        for pdu in pdus {
+            frames.push(self.wrap_tcp(pdu));
+            self.read_batch_ids.push(self.transaction_id);
+        }
+        Ok(frames)
+    }
+
     pub fn create_write_request(&mut self) -> Result<Vec<u8>, ModbusTransportError> {
+        self.check_outstanding(1)?;
         let pdu = self.unit.get_write_request()
             .map_err(ModbusTransportError::Protocol)?;
         Ok(self.wrap_tcp(pdu))
     }
 
+    /// Build a write frame for `data` directly, forcing single or multi write
+    /// per `mode` instead of the builder's `force_multi_write`
+    pub fn create_write_request_using(&mut self, data: &[i32], mode: WriteMode) -> Result<Vec<u8>, ModbusTransportError> {
+        let values = data
+            .iter()
+            .enumerate()
+            .map(|(i, &val)| u16::try_from(val).map_err(|_| ModbusTransportError::ValueOverflow(val, i)))
+            .collect::<Result<Vec<u16>, _>>()?;
+        self.check_outstanding(1)?;
+        let pdu = self.unit.get_write_request_using(&values, mode)
+            .map_err(ModbusTransportError::Protocol)?;
+        Ok(self.wrap_tcp(pdu))
+    }
+
+    /// Generate a fire-and-forget write frame: no response is awaited, so it is
+    /// not tracked against `max_outstanding`. Applies to the write function
+    /// codes 0x05, 0x06, 0x0F and 0x10; read requests always expect a response.
+    pub fn create_write_request_no_ack(&mut self) -> Result<Vec<u8>, ModbusTransportError> {
+        let pdu = self.unit.get_write_request()
+            .map_err(ModbusTransportError::Protocol)?;
+        let transaction_id = self.next_transaction_id();
+        Ok(self.frame_with_transaction_id(transaction_id, pdu))
+    }
+
+    /// Generate one frame per write PDU; several frames in sparse-write mode
+    pub fn create_write_requests(&mut self) -> Result<Vec<Vec<u8>>, ModbusTransportError> {
+        let pdus = self.unit.get_write_requests()
+            .map_err(ModbusTransportError::Protocol)?;
+        self.check_outstanding(pdus.len())?;
+        Ok(pdus.into_iter().map(|pdu| self.wrap_tcp(pdu)).collect())
+    }
+
+    /// Parse the acknowledgements to the frames from `create_write_requests`, in order
+    pub fn parse_write_responses(&self, requests: &[Vec<u8>], frames: &[Vec<u8>]) -> Result<(), ModbusTransportError> {
+        if requests.len() != frames.len() {
+            return Err(ModbusUnitError::ResponseCountMismatch {
+                expected: requests.len(),
+                actual: frames.len(),
+            }.into());
+        }
+        for (request, frame) in requests.iter().zip(frames) {
+            // MBAP header, function code and echoed address
+            if request.len() < 10 {
+                return Err(ModbusTransportError::FrameTooShort);
+            }
+            let transaction_id = ((request[0] as u16) << 8) | (request[1] as u16);
+            let pdu = self.unwrap_tcp_for(frame, Some(transaction_id))?;
+            self.unit.check_write_ack(&pdu, &request[7..])?;
+        }
+        Ok(())
+    }
+
+    /// Frame a Write Single Register (0x06) request for the unit's start address.
+    /// The echoed address and value are verified by `parse_write_single_register_response`.
+    pub fn write_single_register(&mut self, value: i32) -> Result<Vec<u8>, ModbusTransportError> {
+        self.check_outstanding(1)?;
+        let pdu = self.unit.get_write_single_register(value)
+            .map_err(ModbusTransportError::Protocol)?;
+        Ok(self.wrap_tcp(pdu))
+    }
+
+    /// Parse a Write Single Register (0x06) echo, see `ModbusUnit::parse_write_single_register_response`
+    pub fn parse_write_single_register_response(&self, frame: Vec<u8>) -> Result<(), ModbusTransportError> {
+        let pdu = self.unwrap_tcp(&frame)?;
+        self.unit.parse_write_single_register_response(&pdu)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    /// Build a write frame for `values` as floats, see `ModbusUnit::get_f32_write_request`
+    pub fn create_f32_write_request(&mut self, values: &[f32]) -> Result<Vec<u8>, ModbusTransportError> {
+        self.check_outstanding(1)?;
+        let pdu = self.unit.get_f32_write_request(values)
+            .map_err(ModbusTransportError::Protocol)?;
+        Ok(self.wrap_tcp(pdu))
+    }
+
+    /// Parse a register response as floats, see `ModbusUnit::parse_as_f32`
+    pub fn parse_as_f32(&self, frame: Vec<u8>) -> Result<Vec<f32>, ModbusTransportError> {
+        let pdu = self.unwrap_tcp(&frame)?;
+        self.unit.parse_as_f32(&pdu)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    /// Build a write frame for `values` as u32, see `ModbusUnit::get_u32_write_request`
+    pub fn create_u32_write_request(&mut self, values: &[u32]) -> Result<Vec<u8>, ModbusTransportError> {
+        self.check_outstanding(1)?;
+        let pdu = self.unit.get_u32_write_request(values)
+            .map_err(ModbusTransportError::Protocol)?;
+        Ok(self.wrap_tcp(pdu))
+    }
+
+    /// Parse a register response as u32, see `ModbusUnit::parse_as_u32`
+    pub fn parse_as_u32(&self, frame: Vec<u8>) -> Result<Vec<u32>, ModbusTransportError> {
+        let pdu = self.unwrap_tcp(&frame)?;
+        self.unit.parse_as_u32(&pdu)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    /// Build a write frame for `values` as i32, see `ModbusUnit::get_i32_write_request`
+    pub fn create_i32_write_request(&mut self, values: &[i32]) -> Result<Vec<u8>, ModbusTransportError> {
+        self.check_outstanding(1)?;
+        let pdu = self.unit.get_i32_write_request(values)
+            .map_err(ModbusTransportError::Protocol)?;
+        Ok(self.wrap_tcp(pdu))
+    }
+
+    /// Parse a register response as i32, see `ModbusUnit::parse_as_i32`
+    pub fn parse_as_i32(&self, frame: Vec<u8>) -> Result<Vec<i32>, ModbusTransportError> {
+        let pdu = self.unwrap_tcp(&frame)?;
+        self.unit.parse_as_i32(&pdu)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    /// Build a write frame for `values` as u64, see `ModbusUnit::get_u64_write_request`
+    pub fn create_u64_write_request(&mut self, values: &[u64]) -> Result<Vec<u8>, ModbusTransportError> {
+        self.check_outstanding(1)?;
+        let pdu = self.unit.get_u64_write_request(values)
+            .map_err(ModbusTransportError::Protocol)?;
+        Ok(self.wrap_tcp(pdu))
+    }
+
+    /// Parse a register response as u64, see `ModbusUnit::parse_as_u64`
+    pub fn parse_as_u64(&self, frame: Vec<u8>) -> Result<Vec<u64>, ModbusTransportError> {
+        let pdu = self.unwrap_tcp(&frame)?;
+        self.unit.parse_as_u64(&pdu)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    /// Build a write frame for `values` as i64, see `ModbusUnit::get_i64_write_request`
+    pub fn create_i64_write_request(&mut self, values: &[i64]) -> Result<Vec<u8>, ModbusTransportError> {
+        self.check_outstanding(1)?;
+        let pdu = self.unit.get_i64_write_request(values)
+            .map_err(ModbusTransportError::Protocol)?;
+        Ok(self.wrap_tcp(pdu))
+    }
+
+    /// Parse a register response as i64, see `ModbusUnit::parse_as_i64`
+    pub fn parse_as_i64(&self, frame: Vec<u8>) -> Result<Vec<i64>, ModbusTransportError> {
+        let pdu = self.unwrap_tcp(&frame)?;
+        self.unit.parse_as_i64(&pdu)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    /// Build a write frame for `values` as f64, see `ModbusUnit::get_f64_write_request`
+    pub fn create_f64_write_request(&mut self, values: &[f64]) -> Result<Vec<u8>, ModbusTransportError> {
+        self.check_outstanding(1)?;
+        let pdu = self.unit.get_f64_write_request(values)
+            .map_err(ModbusTransportError::Protocol)?;
+        Ok(self.wrap_tcp(pdu))
+    }
+
+    /// Parse a register response as f64, see `ModbusUnit::parse_as_f64`
+    pub fn parse_as_f64(&self, frame: Vec<u8>) -> Result<Vec<f64>, ModbusTransportError> {
+        let pdu = self.unwrap_tcp(&frame)?;
+        self.unit.parse_as_f64(&pdu)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    /// Build a write frame for `text`, see `ModbusUnit::get_string_write_request`
+    pub fn create_string_write_request(&mut self, text: &str) -> Result<Vec<u8>, ModbusTransportError> {
+        self.check_outstanding(1)?;
+        let pdu = self.unit.get_string_write_request(text)
+            .map_err(ModbusTransportError::Protocol)?;
+        Ok(self.wrap_tcp(pdu))
+    }
+
+    /// Parse a register response as text, see `ModbusUnit::parse_as_string`
+    pub fn parse_as_string(&self, frame: Vec<u8>) -> Result<String, ModbusTransportError> {
+        let pdu = self.unwrap_tcp(&frame)?;
+        self.unit.parse_as_string(&pdu)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    /// Frame a Read FIFO Queue (0x18) request
+    pub fn read_fifo_queue(&mut self, fifo_pointer: u16) -> Result<Vec<u8>, ModbusTransportError> {
+        self.check_outstanding(1)?;
+        let pdu = self.unit.get_read_fifo_request(fifo_pointer);
+        Ok(self.wrap_tcp(pdu))
+    }
+
+    /// Parse a Read FIFO Queue (0x18) response into the queued values
+    pub fn parse_fifo_response(&self, frame: Vec<u8>) -> Result<Vec<u16>, ModbusTransportError> {
+        let pdu = self.unwrap_tcp(&frame)?;
+        self.unit.parse_fifo_response(&pdu)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    /// Frame a Read Device Identification (0x2B / 0x0E) request
+    pub fn create_device_identification_request(&mut self, read_device_id_code: u8, object_id: u8) -> Result<Vec<u8>, ModbusTransportError> {
+        self.check_outstanding(1)?;
+        let pdu = self.unit.get_device_identification_request(read_device_id_code, object_id);
+        Ok(self.wrap_tcp(pdu))
+    }
+
+    /// Parse a Read Device Identification (0x2B / 0x0E) response
+    pub fn parse_device_identification(&self, frame: Vec<u8>) -> Result<DeviceIdentification, ModbusTransportError> {
+        let pdu = self.unwrap_tcp(&frame)?;
+        self.unit.parse_device_identification(&pdu)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    /// Frame a Report Server ID (0x11) request
+    pub fn report_server_id(&mut self) -> Result<Vec<u8>, ModbusTransportError> {
+        self.check_outstanding(1)?;
+        let pdu = self.unit.get_report_server_id_request();
+        Ok(self.wrap_tcp(pdu))
+    }
+
+    /// Parse a Report Server ID (0x11) response
+    pub fn parse_report_server_id(&self, frame: Vec<u8>) -> Result<ServerIdReport, ModbusTransportError> {
+        let pdu = self.unwrap_tcp(&frame)?;
+        self.unit.parse_report_server_id(&pdu)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    /// Frame a Diagnostics (0x08) request
+    pub fn create_diagnostics_request(&mut self, sub_function: u16, data: u16) -> Result<Vec<u8>, ModbusTransportError> {
+        self.check_outstanding(1)?;
+        let pdu = self.unit.get_diagnostics_request(sub_function, data);
+        Ok(self.wrap_tcp(pdu))
+    }
+
+    /// Parse a Diagnostics (0x08) response into the echoed sub-function and data word
+    pub fn parse_diagnostics_response(&self, frame: Vec<u8>) -> Result<(u16, u16), ModbusTransportError> {
+        let pdu = self.unwrap_tcp(&frame)?;
+        self.unit.parse_diagnostics_response(&pdu)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    /// Check a Return Query Data loopback response echoes `sent`
+    pub fn check_loopback(&self, frame: Vec<u8>, sent: u16) -> Result<bool, ModbusTransportError> {
+        let pdu = self.unwrap_tcp(&frame)?;
+        self.unit.check_loopback(&pdu, sent)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    /// Frame a Read Exception Status (0x07) request
+    pub fn read_exception_status(&mut self) -> Result<Vec<u8>, ModbusTransportError> {
+        self.check_outstanding(1)?;
+        let pdu = self.unit.get_read_exception_status_request();
+        Ok(self.wrap_tcp(pdu))
+    }
+
+    /// Parse a Read Exception Status (0x07) response into the status byte
+    pub fn parse_exception_status(&self, frame: Vec<u8>) -> Result<u8, ModbusTransportError> {
+        let pdu = self.unwrap_tcp(&frame)?;
+        self.unit.parse_exception_status(&pdu)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    /// Frame a Mask Write Register (0x16) request for the unit's start address.
+    /// The echo is verified by `parse_mask_write_response`.
+    pub fn create_mask_write_request(&mut self, and_mask: u16, or_mask: u16) -> Result<Vec<u8>, ModbusTransportError> {
+        self.check_outstanding(1)?;
+        let pdu = self.unit.get_mask_write_request(and_mask, or_mask)
+            .map_err(ModbusTransportError::Protocol)?;
+        Ok(self.wrap_tcp(pdu))
+    }
+
+    /// Frame a Read/Write Multiple Registers (0x17) request: read `read_qty`
+    /// registers at `read_addr` and write `write_data` at the unit's start address.
+    /// The response is decoded by `parse_read_write_response`.
+    pub fn create_read_write_request(&mut self, read_addr: u16, read_qty: u16, write_data: &[i32]) -> Result<Vec<u8>, ModbusTransportError> {
+        self.check_outstanding(1)?;
+        let pdu = self.unit.get_read_write_request(read_addr, read_qty, write_data)
+            .map_err(ModbusTransportError::Protocol)?;
+        Ok(self.wrap_tcp(pdu))
+    }
+
+    /// Parse a Mask Write Register (0x16) echo, see `ModbusUnit::parse_mask_write_response`
+    pub fn parse_mask_write_response(&self, frame: Vec<u8>) -> Result<(), ModbusTransportError> {
+        let pdu = self.unwrap_tcp(&frame)?;
+        self.unit.parse_mask_write_response(&pdu)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    /// Parse a Read/Write Multiple Registers (0x17) response into the read values
+    pub fn parse_read_write_response(&self, frame: Vec<u8>) -> Result<Vec<u16>, ModbusTransportError> {
+        let pdu = self.unwrap_tcp(&frame)?;
+        self.unit.parse_read_write_response(&pdu)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    /// Frame a caller-built PDU, e.g. a vendor-specific function.
+    /// The PDU is not validated, but the frame counts against `max_outstanding`.
+    pub fn create_raw_request(&mut self, pdu: &[u8]) -> Result<Vec<u8>, ModbusTransportError> {
+        self.check_outstanding(1)?;
+        Ok(self.wrap_tcp(pdu.to_vec()))
+    }
+
+    /// Validate the framing (protocol id, unit id and length) and return the inner PDU
+    /// without any function-specific parsing
+    pub fn unwrap_pdu(&self, frame: &[u8]) -> Result<Vec<u8>, ModbusTransportError> {
+        self.unwrap_tcp(frame)
+    }
+
+    /// Encode `value` into two registers in `order`, stage them and build the
+    /// multi-write frame. The unit must be configured with `length == 2`.
+    pub fn write_f32(&mut self, value: f32, order: WordOrder) -> Result<Vec<u8>, ModbusTransportError> {
+        if self.unit.length() != 2 {
+            return Err(ModbusUnitError::InvalidUnitLength {
+                expected: 2,
+                actual: self.unit.length(),
+            }.into());
+        }
+        for (index, register) in f32_to_registers(value, order).into_iter().enumerate() {
+            self.unit.set(index, register)?;
+        }
+        self.create_write_request()
+    }
+
     /// Parse TCP response and extract values
     pub fn parse_response(&self, frame: Vec<u8>) -> Result<(), ModbusTransportError> {
-        let pdu = self.unwrap_tcp(frame)?;
+        let pdu = self.unwrap_tcp(&frame)?;
+        self.unit.parse_response(&pdu)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    /// Parse a response to the request sent with `txn_id`, for callers
+    /// tracking several in-flight transactions themselves
+    pub fn parse_response_for(&self, txn_id: u16, frame: Vec<u8>) -> Result<(), ModbusTransportError> {
+        let pdu = self.unwrap_tcp_for(&frame, Some(txn_id))?;
         self.unit.parse_response(&pdu)
             .map_err(ModbusTransportError::Protocol)
     }
 
+    /// Transaction ID of the most recent frame
+    pub fn transaction_id(&self) -> u16 {
+        self.transaction_id
+    }
+
+    /// Parse two TCP responses to the same read, accepting the values only if both agree.
+    /// The responses must answer the two latest `create_read_request` frames, in order.
+    pub fn parse_response_verified(&self, first: &[u8], second: &[u8]) -> Result<Vec<u16>, ModbusTransportError> {
+        let [first_id, second_id] = self.recent_read_ids.map(|id| id.unwrap_or(self.expected_transaction_id()));
+        let first_pdu = self.unwrap_tcp_for(first, Some(first_id))?;
+        let second_pdu = self.unwrap_tcp_for(second, Some(second_id))?;
+        self.unit.parse_response_verified(&first_pdu, &second_pdu)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    /// Parse a response, store the values on the unit and return them, see `last_values`
+    pub fn parse_response_cached(&self, frame: Vec<u8>) -> Result<Vec<u16>, ModbusTransportError> {
+        let pdu = self.unwrap_tcp(&frame)?;
+        self.unit.parse_response_cached(&pdu)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    pub fn last_values(&self) -> Option<Vec<u16>> {
+        self.unit.last_values()
+    }
+
+    pub fn last_updated(&self) -> Option<Instant> {
+        self.unit.last_updated()
+    }
+
+    /// Parse the response to `create_read_request_count`
+    pub fn parse_response_count(&self, frame: Vec<u8>, count: u16) -> Result<Vec<u16>, ModbusTransportError> {
+        let pdu = self.unwrap_tcp(&frame)?;
+        self.unit.parse_response_count(&pdu, count)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    /// Parse the response to `create_probe_request`, true if the device answered normally
+    pub fn parse_probe_response(&self, frame: Vec<u8>) -> Result<bool, ModbusTransportError> {
+        let pdu = self.unwrap_tcp(&frame)?;
+        self.unit.parse_probe_response(&pdu)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    /// Parse the responses to `create_read_requests`, in order
+    pub fn parse_responses(&self, frames: &[Vec<u8>]) -> Result<(), ModbusTransportError> {
+        let pdus = frames
+            .iter()
+            .enumerate()
+            .map(|(index, frame)| self.unwrap_tcp_for(frame, self.read_batch_ids.get(index).copied()))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.unit.parse_responses(&pdus)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    /// Parse the responses to `create_read_requests` up to the first failing
+    /// frame, returning the values before it and the index and error where it stopped
+    pub fn parse_responses_partial(&self, frames: &[Vec<u8>]) -> (Vec<u16>, Option<(usize, ModbusTransportError)>) {
+        let mut pdus = Vec::with_capacity(frames.len());
+        let mut unwrap_failure = None;
+        for (index, frame) in frames.iter().enumerate() {
+            match self.unwrap_tcp_for(frame, self.read_batch_ids.get(index).copied()) {
+                Ok(pdu) => pdus.push(pdu),
+                Err(err) => {
+                    unwrap_failure = Some((index, err));
+                    break;
+                }
+            }
+        }
+
+        let (values, unit_failure) = self.unit.parse_responses_partial(&pdus);
+        let failure = match (unit_failure, unwrap_failure) {
+            (Some((index, err)), Some((unwrap_index, _))) if index < unwrap_index => {
+                Some((index, ModbusTransportError::Protocol(err)))
+            }
+            (_, Some(unwrap_failure)) => Some(unwrap_failure),
+            (unit_failure, None) => unit_failure.map(|(index, err)| (index, ModbusTransportError::Protocol(err))),
+        };
+        (values, failure)
+    }
+
+    /// Parse TCP write acknowledgement
+    pub fn parse_write_response(&self, frame: Vec<u8>) -> Result<(), ModbusTransportError> {
+        let pdu = self.unwrap_tcp(&frame)?;
+        self.unit.parse_write_response(&pdu)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    /// Parse TCP write-multiple acknowledgement, returning the echoed quantity
+    pub fn parse_multi_write_response(&self, frame: Vec<u8>) -> Result<u16, ModbusTransportError> {
+        let pdu = self.unwrap_tcp(&frame)?;
+        self.unit.parse_multi_write_response(&pdu)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    /// Parse TCP Write Single Coil echo, returning the echoed coil state
+    pub fn parse_single_coil_write_response(&self, frame: Vec<u8>) -> Result<bool, ModbusTransportError> {
+        let pdu = self.unwrap_tcp(&frame)?;
+        self.unit.parse_single_coil_write_response(&pdu)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    /// Parse several MBAP frames batched into one buffer, returning each frame's values in order.
+    /// Transaction IDs are not checked.
+    pub fn parse_all(&self, buffer: &[u8]) -> Result<Vec<Vec<u16>>, ModbusTransportError> {
+        let mut results = Vec::new();
+        let mut offset = 0;
+
+        while offset < buffer.len() {
+            let rest = &buffer[offset..];
+            if rest.len() < 7 {
+                return Err(ModbusTransportError::TrailingPartialFrame(rest.len()));
+            }
+            let length = self.mbap_length_endianness.u16_from_bytes([rest[4], rest[5]]);
+            let frame_len = 6 + length as usize;
+            if rest.len() < frame_len {
+                return Err(ModbusTransportError::TrailingPartialFrame(rest.len()));
+            }
+
+            let pdu = self.unwrap_tcp_for(&rest[..frame_len], None)?;
+            results.push(self.unit.decode_response(&pdu, self.unit.length())?);
+            offset += frame_len;
+        }
+
+        Ok(results)
+    }
+
+    /// Encode-decode self-test without a socket: build a read request,
+    /// synthesize the response a compliant device would send for `values`
+    /// and run it through `parse_response`
+    pub fn loopback_read(&mut self, values: &[u16]) -> Result<Vec<u16>, ModbusTransportError> {
+        let request = self.create_read_request()?;
+        let transaction_id = ((request[0] as u16) << 8) | (request[1] as u16);
+
+        let pdu = self.unit.encode_read_response(values)?;
+        let response = self.frame_with_transaction_id(transaction_id, pdu);
+        self.parse_response(response)?;
+        Ok(self.unit.values())
+    }
+
     fn wrap_tcp(&mut self, pdu: Vec<u8>) -> Vec<u8> {
-        self.transaction_id = self.transaction_id.wrapping_add(1);
+        self.next_transaction_id();
+        if self.max_outstanding.is_some() {
+            self.outstanding.borrow_mut().push(self.transaction_id);
+        }
+        self.frame_with_transaction_id(self.transaction_id, pdu)
+    }
 
+    /// Advance to the transaction ID of the next frame
+    fn next_transaction_id(&mut self) -> u16 {
+        self.transaction_id = match self.fixed_transaction_id {
+            Some(transaction_id) => transaction_id,
+            None => self.transaction_id.wrapping_add(1),
+        };
+        self.transaction_id
+    }
+
+    /// Refuse `frames` more requests if they would exceed `max_outstanding`
+    fn check_outstanding(&self, frames: usize) -> Result<(), ModbusTransportError> {
+        if let Some(limit) = self.max_outstanding
+            && self.outstanding.borrow().len() + frames > limit
+        {
+            return Err(ModbusTransportError::TooManyOutstanding(limit));
+        }
+        Ok(())
+    }
+
+    /// Number of requests still awaiting a response, tracked only with `max_outstanding`
+    pub fn outstanding(&self) -> usize {
+        self.outstanding.borrow().len()
+    }
+
+    /// Forget all outstanding requests, e.g. after a timeout or reconnect
+    pub fn clear_outstanding(&self) {
+        self.outstanding.borrow_mut().clear();
+    }
+
+    fn frame_with_transaction_id(&self, transaction_id: u16, pdu: Vec<u8>) -> Vec<u8> {
         let length = (pdu.len() + 1) as u16;
         let mut frame = Vec::with_capacity(7 + pdu.len());
 
-        frame.push((self.transaction_id >> 8) as u8);
-        frame.push(self.transaction_id as u8);
+        frame.push((transaction_id >> 8) as u8);
+        frame.push(transaction_id as u8);
         frame.push(0x00);
         frame.push(0x00);
-        frame.push((length >> 8) as u8);
-        frame.push(length as u8);
+        frame.extend(self.mbap_length_endianness.u16_to_bytes(length));
         frame.push(self.device_id);
         frame.extend(pdu);
 
         frame
     }
 
-    fn unwrap_tcp(&self, frame: Vec<u8>) -> Result<Vec<u8>, ModbusTransportError> {
+    fn unwrap_tcp(&self, frame: &[u8]) -> Result<Vec<u8>, ModbusTransportError> {
+        self.unwrap_tcp_for(frame, Some(self.expected_transaction_id()))
+    }
+
+    /// Transaction ID of the latest request
+    fn expected_transaction_id(&self) -> u16 {
+        self.fixed_transaction_id.unwrap_or(self.transaction_id)
+    }
+
+    fn unwrap_tcp_for(&self, frame: &[u8], expected_transaction_id: Option<u16>) -> Result<Vec<u8>, ModbusTransportError> {
         if frame.len() < 7 {
             return Err(ModbusTransportError::FrameTooShort);
         }
 
+        // A response settles its request whether or not it passes the checks below
+        let transaction_id = ((frame[0] as u16) << 8) | (frame[1] as u16);
+        let was_outstanding = {
+            let mut outstanding = self.outstanding.borrow_mut();
+            match outstanding.iter().position(|&id| id == transaction_id) {
+                Some(pos) => {
+                    outstanding.remove(pos);
+                    true
+                }
+                None => false,
+            }
+        };
+
         let protocol_id = ((frame[2] as u16) << 8) | (frame[3] as u16);
         if protocol_id != 0 {
             return Err(ModbusTransportError::InvalidProtocolId(protocol_id));
         }
 
         let unit_id = frame[6];
-        if unit_id != self.device_id {
+        if self.validate_unit_id && unit_id != self.device_id {
             return Err(ModbusTransportError::UnitIdMismatch {
                 expected: self.device_id,
                 received: unit_id,
             });
         }
 
-        let length = ((frame[4] as u16) << 8) | (frame[5] as u16);
+        // The length covers the unit id and at least a function code
+        let length = self.mbap_length_endianness.u16_from_bytes([frame[4], frame[5]]);
         let expected_len = 6 + length as usize;
 
-        if frame.len() < expected_len {
+        if length < 2 || frame.len() < expected_len {
             return Err(ModbusTransportError::FrameTooShort);
         }
 
+        // With `max_outstanding`, any pipelined request may be answered first
+        if let Some(expected) = expected_transaction_id
+            && self.validate_transaction_id
+            && transaction_id != expected
+            && !(self.max_outstanding.is_some() && was_outstanding)
+        {
+            return Err(ModbusTransportError::TransactionIdMismatch {
+                expected,
+                received: transaction_id,
+            });
+        }
+
+        let received_fc = frame[7];
+        if !self.allowed_function_codes.is_empty()
+            && !self.allowed_function_codes.contains(&(received_fc & !function_code::EXCEPTION_OFFSET))
+        {
+            return Err(ModbusTransportError::FunctionCodeNotAllowed(received_fc));
+        }
+
         Ok(frame[7..expected_len].to_vec())
     }
+
     pub fn set(&self, data: &[i32]) -> Result<(), ModbusTransportError> {
         for (i, &val) in data.iter().enumerate() {
             let res = u16::try_from(val).map_err(|_| ModbusTransportError::ValueOverflow(val, i))?;
@@ -162,6 +788,27 @@ impl ModbusTCPUnit {
         Ok(())
     }
 
+    /// Check a read-back of the written range against the staged write values
+    pub fn verify_write(&self, frame: Vec<u8>) -> Result<(), ModbusTransportError> {
+        let pdu = self.unwrap_tcp(&frame)?;
+        self.unit.verify_write(&pdu)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    /// Stage `text` across the unit's registers, padded with the configured pad byte
+    pub fn set_string(&self, text: &str) -> Result<(), ModbusTransportError> {
+        self.unit.set_string(text)
+            .map_err(ModbusTransportError::Protocol)
+    }
+
+    pub fn clear_staging(&self) {
+        self.unit.clear_staging();
+    }
+
+    pub fn is_staging_complete(&self) -> bool {
+        self.unit.is_staging_complete()
+    }
+
     pub fn get(&self) -> Vec<i32> {
         (0..)
             .map(|i| self.unit.get(i))
@@ -169,4 +816,367 @@ impl ModbusTCPUnit {
             .map(|r| r.unwrap() as i32)
             .collect()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn holding_unit(length: i32) -> ModbusTCPUnitBuilder {
+        ModbusTCPUnit::builder()
+            .address(0)
+            .length(length)
+            .register_type(RegisterType::HoldingRegister)
+            .device_id(1)
+    }
+
+    /// MBAP frame answering the latest request with `pdu`
+    fn response(unit: &ModbusTCPUnit, pdu: &[u8]) -> Vec<u8> {
+        unit.frame_with_transaction_id(unit.transaction_id(), pdu.to_vec())
+    }
+
+    #[test]
+    fn write_f32_round_trips_through_parse() {
+        for order in [WordOrder::HighWordFirst, WordOrder::LowWordFirst] {
+            let mut unit = holding_unit(2).word_order(order).build().unwrap();
+            let request = unit.write_f32(-12.375, order).unwrap();
+            assert_eq!(request[7], function_code::WRITE_MULTIPLE_REGISTERS);
+
+            unit.create_read_request().unwrap();
+            let mut pdu = vec![function_code::READ_HOLDING_REGISTERS, 4];
+            pdu.extend(&request[13..17]);
+            assert_eq!(unit.parse_as_f32(response(&unit, &pdu)).unwrap(), vec![-12.375]);
+
+            // The registers stay staged, so a read-back can be verified
+            unit.create_read_request().unwrap();
+            assert!(unit.verify_write(response(&unit, &pdu)).is_ok());
+        }
+    }
+
+    #[test]
+    fn write_f32_requires_two_registers() {
+        let mut unit = holding_unit(3).build().unwrap();
+        assert!(matches!(
+            unit.write_f32(1.0, WordOrder::HighWordFirst),
+            Err(ModbusTransportError::Protocol(ModbusUnitError::InvalidUnitLength { expected: 2, actual: 3 }))
+        ));
+    }
+
+    /// Read response frame for `request` carrying `values`
+    fn read_response(unit: &ModbusTCPUnit, request: &[u8], values: &[u16]) -> Vec<u8> {
+        let transaction_id = ((request[0] as u16) << 8) | (request[1] as u16);
+        let mut pdu = vec![request[7], (values.len() * 2) as u8];
+        pdu.extend(values.iter().flat_map(|value| value.to_be_bytes()));
+        unit.frame_with_transaction_id(transaction_id, pdu)
+    }
+
+    #[test]
+    fn stale_transaction_id_is_rejected() {
+        let mut unit = holding_unit(1).build().unwrap();
+        let first = unit.create_read_request().unwrap();
+        let second = unit.create_read_request().unwrap();
+
+        assert!(matches!(
+            unit.parse_response(read_response(&unit, &first, &[1])),
+            Err(ModbusTransportError::TransactionIdMismatch { expected: 2, received: 1 })
+        ));
+        assert!(unit.parse_response_for(1, read_response(&unit, &first, &[1])).is_ok());
+        assert!(unit.parse_response(read_response(&unit, &second, &[2])).is_ok());
+    }
+
+    #[test]
+    fn transaction_id_check_can_be_disabled() {
+        let mut unit = holding_unit(1).validate_transaction_id(false).build().unwrap();
+        let first = unit.create_read_request().unwrap();
+        unit.create_read_request().unwrap();
+        assert!(unit.parse_response(read_response(&unit, &first, &[1])).is_ok());
+    }
+
+    #[test]
+    fn chunked_responses_match_their_own_ids_despite_interleaving() {
+        let mut unit = holding_unit(200).build().unwrap();
+        let requests = unit.create_read_requests().unwrap();
+        unit.create_raw_request(&[function_code::READ_EXCEPTION_STATUS]).unwrap();
+
+        let frames: Vec<Vec<u8>> = requests
+            .iter()
+            .map(|request| read_response(&unit, request, &vec![7; request[11] as usize]))
+            .collect();
+        assert!(unit.parse_responses(&frames).is_ok());
+
+        let swapped = vec![frames[1].clone(), frames[0].clone()];
+        assert!(matches!(
+            unit.parse_responses(&swapped),
+            Err(ModbusTransportError::TransactionIdMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn verified_responses_match_the_two_latest_reads() {
+        let mut unit = holding_unit(1).build().unwrap();
+        let first = unit.create_read_request().unwrap();
+        unit.create_raw_request(&[function_code::READ_EXCEPTION_STATUS]).unwrap();
+        let second = unit.create_read_request().unwrap();
+
+        let values = unit.parse_response_verified(
+            &read_response(&unit, &first, &[5]),
+            &read_response(&unit, &second, &[5]),
+        );
+        assert_eq!(values.unwrap(), vec![5]);
+    }
+
+    #[test]
+    fn allowed_function_codes_filter_responses() {
+        let mut unit = holding_unit(1)
+            .allowed_function_codes(&[function_code::READ_HOLDING_REGISTERS])
+            .build()
+            .unwrap();
+        let request = unit.create_read_request().unwrap();
+        assert!(unit.parse_response(read_response(&unit, &request, &[1])).is_ok());
+
+        let exception = response(&unit, &[function_code::READ_HOLDING_REGISTERS | function_code::EXCEPTION_OFFSET, 0x02]);
+        assert!(matches!(
+            unit.parse_response(exception),
+            Err(ModbusTransportError::Protocol(ModbusUnitError::ModbusException(0x83, 0x02)))
+        ));
+
+        let disallowed = response(&unit, &[function_code::REPORT_SERVER_ID, 0x01, 0x00]);
+        assert!(matches!(
+            unit.parse_response(disallowed),
+            Err(ModbusTransportError::FunctionCodeNotAllowed(0x11))
+        ));
+    }
+
+    #[test]
+    fn malformed_mbap_length_is_rejected_without_panicking() {
+        let unit = holding_unit(1)
+            .allowed_function_codes(&[function_code::READ_HOLDING_REGISTERS])
+            .build()
+            .unwrap();
+        for length in [0u8, 1] {
+            let frame = vec![0x00, 0x00, 0x00, 0x00, 0x00, length, 0x01, 0x03];
+            assert!(matches!(unit.unwrap_pdu(&frame), Err(ModbusTransportError::FrameTooShort)));
+        }
+        let truncated = vec![0x00, 0x00, 0x00, 0x00, 0x00, 0x05, 0x01, 0x03, 0x02];
+        assert!(matches!(unit.unwrap_pdu(&truncated), Err(ModbusTransportError::FrameTooShort)));
+    }
+
+    #[test]
+    fn mask_write_echo_is_verified_once() {
+        let mut unit = holding_unit(1).build().unwrap();
+        let request = unit.create_mask_write_request(0x00F2, 0x0025).unwrap();
+        let frame = response(&unit, &request[7..]);
+        unit.parse_mask_write_response(frame.clone()).unwrap();
+        assert!(matches!(
+            unit.parse_mask_write_response(frame),
+            Err(ModbusTransportError::Protocol(ModbusUnitError::NoPendingRequest(0x16)))
+        ));
+
+        unit.create_mask_write_request(0x00F2, 0x0025).unwrap();
+        let frame = response(&unit, &[0x16, 0x00, 0x00, 0x00, 0xF2, 0x00, 0x26]);
+        assert!(matches!(
+            unit.parse_mask_write_response(frame),
+            Err(ModbusTransportError::Protocol(ModbusUnitError::MaskEchoMismatch { received_or: 0x26, .. }))
+        ));
+    }
+
+    #[test]
+    fn read_write_response_returns_the_read_block() {
+        let mut unit = holding_unit(2).build().unwrap();
+        unit.create_read_write_request(0, 2, &[7]).unwrap();
+        let frame = response(&unit, &[0x17, 4, 0x00, 0x0A, 0x00, 0x0B]);
+        assert_eq!(unit.parse_read_write_response(frame).unwrap(), vec![0x0A, 0x0B]);
+        assert_eq!(unit.unit.values(), vec![0x0A, 0x0B]);
+    }
+
+    #[test]
+    fn parse_response_does_not_dispatch_write_echoes() {
+        let mut unit = holding_unit(1).build().unwrap();
+        unit.create_mask_write_request(0x00F2, 0x0025).unwrap();
+        let frame = response(&unit, &[0x16, 0x00, 0x00, 0x00, 0xF2, 0x00, 0x25]);
+        assert!(matches!(
+            unit.parse_response(frame),
+            Err(ModbusTransportError::Protocol(ModbusUnitError::UnexpectedFunctionCode(0x03, 0x16)))
+        ));
+    }
+
+    #[test]
+    fn raw_requests_count_against_max_outstanding() {
+        let mut unit = holding_unit(1).max_outstanding(1).build().unwrap();
+        unit.create_raw_request(&[function_code::READ_EXCEPTION_STATUS]).unwrap();
+        assert!(matches!(
+            unit.create_raw_request(&[function_code::READ_EXCEPTION_STATUS]),
+            Err(ModbusTransportError::TooManyOutstanding(1))
+        ));
+    }
+
+    #[test]
+    fn rejected_responses_release_their_outstanding_slot() {
+        let mut unit = holding_unit(1)
+            .max_outstanding(1)
+            .allowed_function_codes(&[function_code::READ_HOLDING_REGISTERS])
+            .build()
+            .unwrap();
+        unit.create_raw_request(&[function_code::READ_EXCEPTION_STATUS]).unwrap();
+        let frame = response(&unit, &[function_code::READ_EXCEPTION_STATUS, 0x00]);
+        assert!(matches!(
+            unit.unwrap_pdu(&frame),
+            Err(ModbusTransportError::FunctionCodeNotAllowed(0x07))
+        ));
+        assert_eq!(unit.outstanding(), 0);
+        assert!(unit.create_read_request().is_ok());
+    }
+
+    #[test]
+    fn multi_write_ack_echoes_the_written_quantity() {
+        let mut unit = holding_unit(3).build().unwrap();
+        unit.set(&[1, 2, 3]).unwrap();
+        let request = unit.create_write_request().unwrap();
+        assert_eq!(&request[8..12], &[0x00, 0x00, 0x00, 0x03]);
+
+        let ack = vec![
+            request[0], request[1], 0x00, 0x00, 0x00, 0x06, 0x01,
+            function_code::WRITE_MULTIPLE_REGISTERS, 0x00, 0x00, 0x00, 0x03,
+        ];
+        assert_eq!(unit.parse_multi_write_response(ack.clone()).unwrap(), 3);
+
+        let mut short_ack = ack;
+        short_ack[11] = 0x02;
+        assert!(matches!(
+            unit.parse_multi_write_response(short_ack),
+            Err(ModbusTransportError::Protocol(ModbusUnitError::QuantityEchoMismatch { expected: 3, received: 2 }))
+        ));
+    }
+
+    #[test]
+    fn unit_id_check_can_be_bypassed() {
+        let mut strict = holding_unit(1).build().unwrap();
+        strict.create_read_request().unwrap();
+        let mut frame = response(&strict, &[function_code::READ_HOLDING_REGISTERS, 0x02, 0x00, 0x05]);
+        frame[6] = 0xFF;
+        assert!(matches!(
+            strict.parse_response(frame.clone()),
+            Err(ModbusTransportError::UnitIdMismatch { expected: 1, received: 0xFF })
+        ));
+
+        let mut lenient = holding_unit(1).validate_unit_id(false).build().unwrap();
+        lenient.create_read_request().unwrap();
+        assert!(lenient.parse_response(frame).is_ok());
+    }
+
+    #[test]
+    fn little_endian_mbap_length_round_trips_only_with_the_flag() {
+        let mut swapped = holding_unit(1)
+            .mbap_length_endianness(Endianness::Little)
+            .build()
+            .unwrap();
+        let request = swapped.create_read_request().unwrap();
+        assert_eq!(&request[4..6], &[0x06, 0x00]);
+
+        let frame = response(&swapped, &[function_code::READ_HOLDING_REGISTERS, 0x02, 0x00, 0x05]);
+        assert_eq!(&frame[4..6], &[0x05, 0x00]);
+        assert!(swapped.parse_response(frame.clone()).is_ok());
+
+        let mut spec = holding_unit(1).build().unwrap();
+        spec.create_read_request().unwrap();
+        assert!(matches!(
+            spec.parse_response(frame),
+            Err(ModbusTransportError::FrameTooShort)
+        ));
+    }
+
+    #[test]
+    fn loopback_read_returns_the_synthesized_values() {
+        let mut unit = holding_unit(3).build().unwrap();
+        assert_eq!(unit.loopback_read(&[1, 0xBEEF, 3]).unwrap(), vec![1, 0xBEEF, 3]);
+
+        let mut coils = ModbusTCPUnit::builder()
+            .address(0)
+            .length(9)
+            .register_type(RegisterType::CoilRegister)
+            .device_id(1)
+            .build()
+            .unwrap();
+        let values = [1, 0, 1, 1, 0, 0, 0, 0, 1];
+        assert_eq!(coils.loopback_read(&values).unwrap(), values);
+    }
+
+    #[test]
+    fn no_ack_writes_do_not_count_against_max_outstanding() {
+        let mut unit = holding_unit(1).max_outstanding(1).build().unwrap();
+        unit.set(&[5]).unwrap();
+        for _ in 0..3 {
+            let frame = unit.create_write_request_no_ack().unwrap();
+            assert_eq!(frame[7], function_code::WRITE_SINGLE_REGISTER);
+        }
+        assert_eq!(unit.outstanding(), 0);
+        assert!(unit.create_read_request().is_ok());
+    }
+
+    #[test]
+    fn sparse_write_acks_match_their_own_frames() {
+        let mut unit = holding_unit(4).sparse_writes(true).build().unwrap();
+        unit.unit.set(0, 10).unwrap();
+        unit.unit.set(3, 40).unwrap();
+        let requests = unit.create_write_requests().unwrap();
+        assert_eq!(requests.len(), 2);
+
+        // A single write is acknowledged by echoing the request
+        assert!(unit.parse_write_responses(&requests, &requests).is_ok());
+        let swapped = vec![requests[1].clone(), requests[0].clone()];
+        assert!(matches!(
+            unit.parse_write_responses(&requests, &swapped),
+            Err(ModbusTransportError::TransactionIdMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn parse_all_splits_batched_frames() {
+        let unit = holding_unit(1).build().unwrap();
+        let mut buffer = unit.frame_with_transaction_id(1, vec![function_code::READ_HOLDING_REGISTERS, 0x02, 0x00, 0x07]);
+        buffer.extend(unit.frame_with_transaction_id(2, vec![function_code::READ_HOLDING_REGISTERS, 0x02, 0x01, 0x00]));
+        assert_eq!(unit.parse_all(&buffer).unwrap(), vec![vec![7], vec![256]]);
+        assert_eq!(unit.parse_all(&[]).unwrap(), Vec::<Vec<u16>>::new());
+
+        let mut partial = buffer.clone();
+        partial.extend(&buffer[..9]);
+        assert!(matches!(
+            unit.parse_all(&partial),
+            Err(ModbusTransportError::TrailingPartialFrame(9))
+        ));
+        assert!(matches!(
+            unit.parse_all(&buffer[..5]),
+            Err(ModbusTransportError::TrailingPartialFrame(5))
+        ));
+    }
+
+    #[test]
+    fn parse_all_rejects_mbap_length_below_two() {
+        let unit = holding_unit(1).build().unwrap();
+        for length in [0u8, 1] {
+            let mut buffer = vec![0x00, 0x01, 0x00, 0x00, 0x00, length, 0x01];
+            buffer.extend(unit.frame_with_transaction_id(2, vec![function_code::READ_HOLDING_REGISTERS, 0x02, 0x00, 0x07]));
+            assert!(
+                matches!(unit.parse_all(&buffer), Err(ModbusTransportError::FrameTooShort)),
+                "length {length}"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_all_reads_the_configured_mbap_length_endianness() {
+        let swapped = holding_unit(1)
+            .mbap_length_endianness(Endianness::Little)
+            .build()
+            .unwrap();
+        let mut buffer = swapped.frame_with_transaction_id(1, vec![function_code::READ_HOLDING_REGISTERS, 0x02, 0x00, 0x07]);
+        buffer.extend(swapped.frame_with_transaction_id(2, vec![function_code::READ_HOLDING_REGISTERS, 0x02, 0x00, 0x08]));
+        assert_eq!(swapped.parse_all(&buffer).unwrap(), vec![vec![7], vec![8]]);
+
+        let spec = holding_unit(1).build().unwrap();
+        assert!(matches!(
+            spec.parse_all(&buffer),
+            Err(ModbusTransportError::TrailingPartialFrame(_))
+        ));
+    }
+}
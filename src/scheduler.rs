@@ -0,0 +1,68 @@
+use std::time::{Duration, Instant};
+use super::*;
+
+struct ScheduledUnit {
+    unit: ModbusUnit,
+    interval: Duration,
+    next_due: Option<Instant>,
+}
+
+/// Decides which unit should be polled next. Performs no I/O.
+pub struct PollScheduler {
+    units: Vec<ScheduledUnit>,
+}
+
+impl Default for PollScheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PollScheduler {
+    pub fn new() -> Self {
+        PollScheduler { units: Vec::new() }
+    }
+
+    /// Register a unit polled every `interval`. New units are due immediately.
+    pub fn add(&mut self, unit: ModbusUnit, interval: Duration) -> &mut Self {
+        self.units.push(ScheduledUnit {
+            unit,
+            interval,
+            next_due: None,
+        });
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.units.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.units.is_empty()
+    }
+
+    /// Return the most overdue unit at `now` and reschedule it one interval later.
+    pub fn next_due(&mut self, now: Instant) -> Option<&ModbusUnit> {
+        let index = self.units
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| s.next_due.is_none_or(|due| due <= now))
+            .min_by_key(|(_, s)| s.next_due)
+            .map(|(i, _)| i)?;
+
+        let scheduled = &mut self.units[index];
+        scheduled.next_due = Some(now + scheduled.interval);
+        Some(&scheduled.unit)
+    }
+
+    /// Time until the next unit becomes due, `Duration::ZERO` if one already is.
+    pub fn time_until_next(&self, now: Instant) -> Option<Duration> {
+        self.units
+            .iter()
+            .map(|s| match s.next_due {
+                Some(due) => due.saturating_duration_since(now),
+                None => Duration::ZERO,
+            })
+            .min()
+    }
+}
@@ -0,0 +1,238 @@
+use std::collections::HashMap;
+use super::*;
+
+/// Wire type of a schema field
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FieldType {
+    U16,
+    I16,
+    U32,
+    F32,
+}
+
+impl FieldType {
+    /// Number of registers the field occupies
+    pub fn width(&self) -> usize {
+        match self {
+            FieldType::U16 | FieldType::I16 => 1,
+            FieldType::U32 | FieldType::F32 => 2,
+        }
+    }
+
+    /// Decode the field from `regs`, which must hold at least `width()` registers
+    pub(crate) fn decode(&self, regs: &[u16], order: WordOrder) -> Value {
+        match self {
+            FieldType::U16 => Value::U16(regs[0]),
+            FieldType::I16 => Value::I16(regs[0] as i16),
+            FieldType::U32 => Value::U32(registers_to_u32([regs[0], regs[1]], order)),
+            FieldType::F32 => Value::F32(registers_to_f32([regs[0], regs[1]], order)),
+        }
+    }
+}
+
+/// Decoded field value
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Value {
+    U16(u16),
+    I16(i16),
+    U32(u32),
+    F32(f32),
+}
+
+struct SchemaField {
+    name: String,
+    offset: usize,
+    field_type: FieldType,
+    order: WordOrder,
+}
+
+/// Maps register offsets within a unit to named, typed fields
+#[derive(Default)]
+pub struct Schema {
+    fields: Vec<SchemaField>,
+}
+
+impl Schema {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declare a field starting `offset` registers into the unit
+    pub fn add(&mut self, name: impl Into<String>, offset: usize, field_type: FieldType, order: WordOrder) -> &mut Self {
+        self.fields.push(SchemaField {
+            name: name.into(),
+            offset,
+            field_type,
+            order,
+        });
+        self
+    }
+}
+
+/// Ordered field list parsed from a compact descriptor such as
+/// `"u16,i16,f32:cdab,u32:big"`. Word order suffixes are `abcd`/`big`
+/// (high word first, the default) and `cdab`/`little` (low word first).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Layout {
+    fields: Vec<(FieldType, WordOrder)>,
+}
+
+impl Layout {
+    pub fn parse(spec: &str) -> Result<Layout, ModbusUnitError> {
+        let fields = spec
+            .split(',')
+            .map(|token| {
+                let token = token.trim();
+                let invalid = || ModbusUnitError::InvalidLayout(token.to_string());
+                let (type_name, order_name) = match token.split_once(':') {
+                    Some((type_name, order_name)) => (type_name, Some(order_name)),
+                    None => (token, None),
+                };
+                let field_type = match type_name.to_ascii_lowercase().as_str() {
+                    "u16" => FieldType::U16,
+                    "i16" => FieldType::I16,
+                    "u32" => FieldType::U32,
+                    "f32" => FieldType::F32,
+                    _ => return Err(invalid()),
+                };
+                let order = match order_name.map(str::to_ascii_lowercase).as_deref() {
+                    None | Some("abcd") | Some("big") => WordOrder::HighWordFirst,
+                    Some("cdab") | Some("little") => WordOrder::LowWordFirst,
+                    Some(_) => return Err(invalid()),
+                };
+                Ok((field_type, order))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Layout { fields })
+    }
+
+    /// Total number of registers the layout covers
+    pub fn width(&self) -> usize {
+        self.fields.iter().map(|(field_type, _)| field_type.width()).sum()
+    }
+}
+
+impl ModbusUnit {
+    /// Parse a register response and decode it field by field per `layout`,
+    /// which must cover exactly `length` registers
+    pub fn decode_layout(&self, pdu: &[u8], layout: &Layout) -> Result<Vec<Value>, ModbusUnitError> {
+        if self.is_bit_data() {
+            return Err(ModbusUnitError::NotRegisterData(self.register_type()));
+        }
+        if layout.width() != self.length() as usize {
+            return Err(ModbusUnitError::InvalidUnitLength {
+                expected: layout.width() as u16,
+                actual: self.length(),
+            });
+        }
+        let values = self.decode_response(pdu, self.length())?;
+
+        let mut offset = 0;
+        let mut result = Vec::with_capacity(layout.fields.len());
+        for &(field_type, order) in &layout.fields {
+            let end = offset + field_type.width();
+            result.push(field_type.decode(&values[offset..end], order));
+            offset = end;
+        }
+        self.store_values(&values);
+        Ok(result)
+    }
+
+    /// Parse a register response and decode every field declared in `schema`
+    pub fn parse_with_schema(&self, pdu: &[u8], schema: &Schema) -> Result<HashMap<String, Value>, ModbusUnitError> {
+        if self.is_bit_data() {
+            return Err(ModbusUnitError::NotRegisterData(self.register_type()));
+        }
+        let values = self.decode_response(pdu, self.length())?;
+
+        let mut result = HashMap::with_capacity(schema.fields.len());
+        for field in &schema.fields {
+            let end = field.offset + field.field_type.width();
+            if end > values.len() {
+                return Err(ModbusUnitError::ReadIndexOutOfRange(end - 1));
+            }
+            let value = field.field_type.decode(&values[field.offset..end], field.order);
+            result.insert(field.name.clone(), value);
+        }
+        self.store_values(&values);
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn holding_unit(length: i32) -> ModbusUnit {
+        let mut builder = ModbusUnit::builder();
+        builder.address(0).length(length).register_type(RegisterType::HoldingRegister);
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn layout_parse_reads_types_and_word_orders() {
+        let layout = Layout::parse("u16, I16,f32:cdab,u32:big,u32:LITTLE,f32:abcd").unwrap();
+        assert_eq!(
+            layout.fields,
+            vec![
+                (FieldType::U16, WordOrder::HighWordFirst),
+                (FieldType::I16, WordOrder::HighWordFirst),
+                (FieldType::F32, WordOrder::LowWordFirst),
+                (FieldType::U32, WordOrder::HighWordFirst),
+                (FieldType::U32, WordOrder::LowWordFirst),
+                (FieldType::F32, WordOrder::HighWordFirst),
+            ]
+        );
+        assert_eq!(layout.width(), 10);
+    }
+
+    #[test]
+    fn layout_parse_rejects_unknown_tokens() {
+        for spec in ["u8", "u16,", "f32:dcba", "u32:"] {
+            assert!(
+                matches!(Layout::parse(spec), Err(ModbusUnitError::InvalidLayout(_))),
+                "{spec}"
+            );
+        }
+    }
+
+    #[test]
+    fn decode_layout_requires_matching_width() {
+        let unit = holding_unit(3);
+        let pdu = [0x03, 0x06, 0x00, 0x01, 0x00, 0x02, 0x00, 0x03];
+        let narrow = Layout::parse("u16,u16").unwrap();
+        assert!(matches!(
+            unit.decode_layout(&pdu, &narrow),
+            Err(ModbusUnitError::InvalidUnitLength { expected: 2, actual: 3 })
+        ));
+
+        let exact = Layout::parse("i16,u32:cdab").unwrap();
+        assert_eq!(
+            unit.decode_layout(&[0x03, 0x06, 0xFF, 0xFF, 0x00, 0x02, 0x00, 0x03], &exact).unwrap(),
+            vec![Value::I16(-1), Value::U32(0x0003_0002)]
+        );
+    }
+
+    #[test]
+    fn parse_with_schema_decodes_named_fields() {
+        let unit = holding_unit(4);
+        let mut schema = Schema::new();
+        schema
+            .add("count", 0, FieldType::U16, WordOrder::HighWordFirst)
+            .add("temperature", 1, FieldType::F32, WordOrder::HighWordFirst)
+            .add("offset", 3, FieldType::I16, WordOrder::HighWordFirst);
+        let pdu = [0x03, 0x08, 0x00, 0x05, 0x41, 0xC8, 0x00, 0x00, 0xFF, 0xFE];
+
+        let fields = unit.parse_with_schema(&pdu, &schema).unwrap();
+        assert_eq!(fields["count"], Value::U16(5));
+        assert_eq!(fields["temperature"], Value::F32(25.0));
+        assert_eq!(fields["offset"], Value::I16(-2));
+        assert_eq!(unit.values(), vec![5, 0x41C8, 0, 0xFFFE]);
+
+        schema.add("overflow", 3, FieldType::U32, WordOrder::HighWordFirst);
+        assert!(matches!(
+            unit.parse_with_schema(&pdu, &schema),
+            Err(ModbusUnitError::ReadIndexOutOfRange(4))
+        ));
+    }
+}
@@ -0,0 +1,93 @@
+use std::collections::BTreeMap;
+use super::*;
+
+/// Counters collected by [`SessionAnalyzer`]
+#[derive(Clone, Debug, Default)]
+pub struct SessionStats {
+    pub frames: usize,
+    pub valid: usize,
+    pub too_short: usize,
+    pub crc_errors: usize,
+    pub exceptions: usize,
+    /// Valid (non-exception) frames per function code
+    pub valid_by_function_code: BTreeMap<u8, usize>,
+    /// Exception frames per originating function code (without the 0x80 bit)
+    pub exceptions_by_function_code: BTreeMap<u8, usize>,
+}
+
+/// Post-mortem summary of a recorded sequence of RTU frames
+#[derive(Default)]
+pub struct SessionAnalyzer {
+    stats: SessionStats,
+}
+
+impl SessionAnalyzer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Validate one captured RTU frame and account for it in the stats
+    pub fn push(&mut self, frame: &[u8]) -> &mut Self {
+        self.stats.frames += 1;
+
+        match ModbusRTU::check_crc(frame) {
+            Ok(()) => {
+                let function_code = frame[1];
+                if (function_code & function_code::EXCEPTION_OFFSET) != 0 {
+                    self.stats.exceptions += 1;
+                    *self.stats.exceptions_by_function_code
+                        .entry(function_code & !function_code::EXCEPTION_OFFSET)
+                        .or_insert(0) += 1;
+                } else {
+                    self.stats.valid += 1;
+                    *self.stats.valid_by_function_code
+                        .entry(function_code)
+                        .or_insert(0) += 1;
+                }
+            }
+            Err(ModbusTransportError::CrcMismatch { .. }) => self.stats.crc_errors += 1,
+            Err(_) => self.stats.too_short += 1,
+        }
+        self
+    }
+
+    pub fn stats(&self) -> &SessionStats {
+        &self.stats
+    }
+
+    pub fn into_stats(self) -> SessionStats {
+        self.stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_are_counted_by_kind() {
+        let unit = ModbusRTU::builder()
+            .address(0)
+            .length(1)
+            .register_type(RegisterType::HoldingRegister)
+            .device_id(1)
+            .build()
+            .unwrap();
+        let read = unit.create_read_request().unwrap();
+        let mut exception = read.clone();
+        exception.truncate(2);
+        exception[1] |= function_code::EXCEPTION_OFFSET;
+        exception.push(0x02);
+        exception.extend([0, 0]);
+        ModbusRTU::finalize_frame(&mut exception).unwrap();
+        let mut corrupt = read.clone();
+        corrupt[2] ^= 0xFF;
+
+        let mut analyzer = SessionAnalyzer::new();
+        analyzer.push(&read).push(&exception).push(&corrupt).push(&[0x01]);
+        let stats = analyzer.into_stats();
+        assert_eq!((stats.frames, stats.valid, stats.exceptions), (4, 1, 1));
+        assert_eq!((stats.crc_errors, stats.too_short), (1, 1));
+        assert_eq!(stats.exceptions_by_function_code.get(&function_code::READ_HOLDING_REGISTERS), Some(&1));
+    }
+}
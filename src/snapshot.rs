@@ -0,0 +1,54 @@
+use std::time::Duration;
+use super::*;
+
+/// Addressing part of a unit's configuration
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UnitConfig {
+    pub start_addr: u16,
+    pub length: u16,
+    pub register_type: RegisterType,
+}
+
+/// Point-in-time view of a unit: its configuration and the values of the
+/// last parsed read response. Serializable with the `serde` feature.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UnitSnapshot {
+    pub config: UnitConfig,
+    pub last_values: Vec<u16>,
+    /// Time since `last_values` were stored, `None` if never parsed
+    pub age: Option<Duration>,
+}
+
+impl ModbusUnit {
+    pub fn snapshot(&self) -> UnitSnapshot {
+        UnitSnapshot {
+            config: UnitConfig {
+                start_addr: self.start_addr(),
+                length: self.length(),
+                register_type: self.register_type(),
+            },
+            last_values: self.values(),
+            age: self.last_updated().map(|updated| updated.elapsed()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshot_age_follows_any_stored_values() {
+        let mut builder = ModbusUnit::builder();
+        builder.address(0).length(1).register_type(RegisterType::HoldingRegister);
+        let unit = builder.build().unwrap();
+        assert_eq!(unit.snapshot().age, None);
+
+        unit.parse_response(&[0x03, 0x02, 0x00, 0x2A]).unwrap();
+        let snapshot = unit.snapshot();
+        assert_eq!(snapshot.last_values, vec![42]);
+        assert!(snapshot.age.is_some());
+    }
+}
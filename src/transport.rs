@@ -0,0 +1,99 @@
+use super::*;
+
+/// A connection able to carry one Modbus request PDU and return the
+/// response PDU, e.g. a TCP socket or a serial port with its own framing.
+/// Lets a `ModbusUnit` drive any connection through `ModbusUnit::read` and
+/// `ModbusUnit::write`.
+pub trait Transport {
+    /// Send `request_pdu` and block until the matching response PDU arrives
+    fn transact(&mut self, request_pdu: &[u8]) -> Result<Vec<u8>, ModbusTransportError>;
+}
+
+impl ModbusUnit {
+    /// Read the whole unit over `transport`, splitting large reads into
+    /// several requests, and return the stored values.
+    ///
+    /// `transport` is borrowed mutably only for the duration of the call; the
+    /// unit keeps no reference to it, so one transport can serve many units
+    /// and a unit can move between transports freely.
+    pub fn read(&self, transport: &mut dyn Transport) -> Result<Vec<u16>, ModbusTransportError> {
+        let requests = self.create_read_requests()?;
+        let responses = requests
+            .iter()
+            .map(|request| transport.transact(request))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.parse_responses(&responses)?;
+        Ok(self.values())
+    }
+
+    /// Read one value at `start_addr` over `transport` to test connectivity,
+    /// see `parse_probe_response`. A transport timeout means the device is absent.
+    /// `transport` is borrowed only for the call, as with `read`.
+    pub fn probe(&self, transport: &mut dyn Transport) -> Result<bool, ModbusTransportError> {
+        let response = transport.transact(&self.create_probe_request()?)?;
+        Ok(self.parse_probe_response(&response)?)
+    }
+
+    /// Send the staged write values over `transport` and check each acknowledgement.
+    /// `transport` is borrowed only for the call, as with `read`.
+    pub fn write(&self, transport: &mut dyn Transport) -> Result<(), ModbusTransportError> {
+        for request in self.get_write_requests()? {
+            let response = transport.transact(&request)?;
+            self.check_write_ack(&response, &request)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+
+    /// Answers each request with the next canned response and records what was sent
+    #[derive(Default)]
+    struct Loopback {
+        sent: Vec<Vec<u8>>,
+        responses: VecDeque<Vec<u8>>,
+    }
+
+    impl Transport for Loopback {
+        fn transact(&mut self, request_pdu: &[u8]) -> Result<Vec<u8>, ModbusTransportError> {
+            self.sent.push(request_pdu.to_vec());
+            self.responses.pop_front().ok_or(ModbusTransportError::ReadTimeout { received: 0, expected: 1 })
+        }
+    }
+
+    fn holding_unit(length: i32) -> ModbusUnit {
+        let mut builder = ModbusUnit::builder();
+        builder.address(10).length(length).register_type(RegisterType::HoldingRegister);
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn unit_reads_probes_and_writes_over_any_transport() {
+        let unit = holding_unit(2);
+        let mut transport = Loopback::default();
+
+        transport.responses.push_back(vec![0x03, 0x04, 0x00, 0x01, 0x00, 0x02]);
+        assert_eq!(unit.read(&mut transport).unwrap(), vec![1, 2]);
+        assert_eq!(transport.sent[0], vec![0x03, 0x00, 0x0A, 0x00, 0x02]);
+
+        transport.responses.push_back(vec![0x03, 0x02, 0x00, 0x01]);
+        assert!(unit.probe(&mut transport).unwrap());
+
+        unit.set(0, 5).unwrap();
+        unit.set(1, 6).unwrap();
+        transport.responses.push_back(vec![0x10, 0x00, 0x0A, 0x00, 0x02]);
+        unit.write(&mut transport).unwrap();
+        assert_eq!(transport.sent[2][..6], [0x10, 0x00, 0x0A, 0x00, 0x02, 0x04]);
+
+        // A unit can move to another transport between calls
+        let mut other = Loopback::default();
+        other.responses.push_back(vec![0x83, 0x02]);
+        assert!(matches!(
+            unit.read(&mut other),
+            Err(ModbusTransportError::Protocol(ModbusUnitError::ModbusException(0x83, 0x02)))
+        ));
+    }
+}